@@ -1,14 +1,18 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 use snafu::{prelude::*, whatever};
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
+use queryscript::ast::Pretty;
 use queryscript::compile;
+use queryscript::compile::schema::TestKind;
 use queryscript::error::*;
 use queryscript::parser;
 use queryscript::parser::error::PrettyError;
 use queryscript::runtime;
+use queryscript::types::Value;
 
 mod repl;
 mod rustyline;
@@ -17,6 +21,9 @@ mod rustyline;
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     file: Option<String>,
 
     #[arg(short, long, default_value_t = false)]
@@ -37,9 +44,35 @@ struct Cli {
     #[arg(long)]
     no_inlining: bool,
 
+    /// Additional directories to search for imported schemas
+    #[arg(short = 'I', long = "search-path")]
+    search_path: Vec<String>,
+
     /// Ignore compilation errors and continue executing queries
     #[arg(long)]
     ignore_errors: bool,
+
+    /// Values for placeholders and externs, e.g. --param start=2023-01-01
+    #[arg(long = "param", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run a schema's expressions (the same as passing the file directly, but unambiguous for
+    /// files named like a subcommand)
+    Run { file: String },
+    /// Run the tests and assertions declared in a schema
+    Test { file: String },
+    /// Print a Markdown reference of a schema's declarations and their doc comments
+    Docs { file: String },
+}
+
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    match param.split_once('=') {
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err(format!("expected <name>=<value>, got {:?}", param)),
+    }
 }
 
 enum Mode {
@@ -53,6 +86,7 @@ fn main() {
         Ok(()) => {}
         Err(e) => {
             eprintln!("{}", e);
+            std::process::exit(1);
         }
     }
 }
@@ -77,7 +111,55 @@ fn main_result() -> Result<(), QSError> {
 
     let engine_type = queryscript::runtime::SQLEngineType::from_name(&cli.engine).unwrap();
 
-    match cli.file {
+    if let Some(Command::Docs { file }) = &cli.command {
+        let compiler = compile::Compiler::new_with_config(compile::CompilerConfig {
+            search_path: cli.search_path,
+            engine: engine_type,
+            ..Default::default()
+        })?;
+        let schema_result = compiler.compile_schema_from_file(&Path::new(file));
+        let contents = compiler.file_contents()?;
+        for (_idx, err) in schema_result.errors.iter() {
+            eprintln!("{}", err.pretty_with_code(&contents.files));
+        }
+        if let Some(schema) = schema_result.result {
+            print!("{}", compile::docs::schema_reference(&*schema.read()?)?);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Test { file }) = &cli.command {
+        let rt = runtime::build().context(RuntimeSnafu {
+            file: file.to_string(),
+        })?;
+
+        let compiler = compile::Compiler::new_with_config(compile::CompilerConfig {
+            allow_inlining: !cli.no_inlining,
+            search_path: cli.search_path,
+            engine: engine_type,
+            ..Default::default()
+        })?;
+        return match run_tests(compiler.clone(), &rt, engine_type, file, &cli.params) {
+            Err(err) => {
+                let contents = compiler.file_contents()?;
+                let err_strs = err
+                    .format_without_backtrace()
+                    .iter()
+                    .map(|e| e.pretty_with_code(&contents.files))
+                    .collect::<Vec<_>>();
+                whatever!("{}", err_strs.join("\n"))
+            }
+            Ok(0) => Ok(()),
+            Ok(failed) => whatever!("{} test(s) failed", failed),
+        };
+    }
+
+    let file = match cli.command {
+        Some(Command::Run { file }) => Some(file),
+        _ => cli.file,
+    };
+
+    match file {
         Some(file) => {
             let rt = runtime::build().context(RuntimeSnafu {
                 file: file.to_string(),
@@ -85,6 +167,8 @@ fn main_result() -> Result<(), QSError> {
 
             let compiler = compile::Compiler::new_with_config(compile::CompilerConfig {
                 allow_inlining: !cli.no_inlining,
+                search_path: cli.search_path,
+                engine: engine_type,
                 ..Default::default()
             })?;
             match run_file(
@@ -95,6 +179,7 @@ fn main_result() -> Result<(), QSError> {
                 mode,
                 cli.execute,
                 cli.ignore_errors,
+                &cli.params,
             ) {
                 Err(err) => {
                     let errs = if cli.verbose {
@@ -126,7 +211,7 @@ fn main_result() -> Result<(), QSError> {
                 file: "<repl>".to_string(),
             })?;
 
-            crate::repl::run(&rt, engine_type);
+            crate::repl::run(&rt, engine_type, cli.search_path, cli.params);
             Ok(())
         }
     }
@@ -140,6 +225,7 @@ fn run_file(
     mode: Mode,
     execute: Option<String>,
     ignore_errors: bool,
+    params: &[(String, String)],
 ) -> Result<(), QSError> {
     let path = Path::new(&file);
     if !path.exists() {
@@ -211,7 +297,12 @@ fn run_file(
         return Ok(());
     }
 
-    let ctx = queryscript::runtime::Context::new(&schema, engine_type);
+    let mut ctx = queryscript::runtime::Context::new(&schema, engine_type);
+    let unbound = bind_params(rt, &mut ctx, &schema, params, file)?;
+    if unbound.len() > 0 {
+        whatever!("No such extern: {}", unbound.join(", "));
+    }
+
     let locked_schema = schema.read()?;
     for expr in locked_schema.exprs.iter() {
         let expr = expr.to_runtime_type().context(RuntimeSnafu {
@@ -227,3 +318,157 @@ fn run_file(
 
     Ok(())
 }
+
+// Runs the tests and assertions in a schema (which are skipped during normal execution), printing
+// whether each one passed along with its location. Returns the number of tests that failed.
+//
+fn run_tests(
+    compiler: compile::Compiler,
+    rt: &runtime::Runtime,
+    engine_type: queryscript::runtime::SQLEngineType,
+    file: &str,
+    params: &[(String, String)],
+) -> Result<usize, QSError> {
+    let path = Path::new(&file);
+    if !path.exists() {
+        whatever!("Path {:?} does not exist", path);
+    }
+
+    let schema = compiler
+        .compile_schema_from_file(&path)
+        .as_result()?
+        .unwrap();
+
+    let mut ctx = queryscript::runtime::Context::new(&schema, engine_type);
+    let unbound = bind_params(rt, &mut ctx, &schema, params, file)?;
+    if unbound.len() > 0 {
+        whatever!("No such extern: {}", unbound.join(", "));
+    }
+
+    let mut total = 0;
+    let mut failed = 0;
+    for schema in test_schemas(&schema)? {
+        let locked_schema = schema.read()?;
+        total += locked_schema.tests.len();
+        for test in locked_schema.tests.iter() {
+            let name = match &test.kind {
+                TestKind::ExpectEmpty { name } => format!("test {:?}", name),
+                TestKind::Assert => "assert".to_string(),
+            };
+
+            // A test that can't be run (e.g. because its query fails) is reported as an error,
+            // and doesn't stop the rest of the tests from running.
+            //
+            match run_test(rt, &ctx, test, file) {
+                Ok((true, _)) => {
+                    println!("{} {} {}", "PASS".green().bold(), test.pretty(), name);
+                }
+                Ok((false, value)) => {
+                    failed += 1;
+                    println!("{} {} {}", "FAIL".red().bold(), test.pretty(), name);
+                    if let Value::Relation(_) = &value {
+                        println!("{}", value);
+                    }
+                }
+                Err(err) => {
+                    failed += 1;
+                    println!("{} {} {}", "ERROR".red().bold(), test.pretty(), name);
+                    println!("{}", err);
+                }
+            }
+        }
+    }
+
+    println!("{} passed, {} failed", total - failed, failed);
+    Ok(failed)
+}
+
+// Runs a single test, returning whether it passed along with the value it evaluated to.
+//
+fn run_test(
+    rt: &runtime::Runtime,
+    ctx: &runtime::Context,
+    test: &compile::schema::Located<compile::schema::SchemaTest>,
+    file: &str,
+) -> Result<(bool, Value), QSError> {
+    let expr = test.expr.to_runtime_type().context(RuntimeSnafu {
+        file: file.to_string(),
+    })?;
+    let value = rt
+        .block_on(async { runtime::eval(ctx, &expr).await })
+        .context(RuntimeSnafu {
+            file: file.to_string(),
+        })?;
+
+    let passed = match (&test.kind, &value) {
+        (TestKind::ExpectEmpty { .. }, Value::Relation(r)) => r
+            .records()
+            .map_err(runtime::RuntimeError::from)
+            .context(RuntimeSnafu {
+                file: file.to_string(),
+            })?
+            .is_empty(),
+        (TestKind::Assert, Value::Boolean(b)) => *b,
+        (TestKind::Assert, Value::Null) => false,
+        (_, value) => whatever!("Unexpected test result: {}", value),
+    };
+    Ok((passed, value))
+}
+
+// The schemas whose tests are run by `qs test`: the schema itself, followed by the schemas that it
+// (transitively) imports. Parameterized imports contribute each of their instances, since their
+// tests depend on the arguments they were imported with. Each schema is only included once, no
+// matter how many times it's imported.
+//
+fn test_schemas(schema: &compile::SchemaRef) -> Result<Vec<compile::SchemaRef>, QSError> {
+    let mut schemas = vec![schema.clone()];
+    let mut i = 0;
+    while i < schemas.len() {
+        let imports = schemas[i]
+            .read()?
+            .imports
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for imported in imports {
+            let imported = imported.read()?;
+            let imported = match &imported.instances {
+                Some(instances) if instances.len() > 0 => instances.clone(),
+                _ => vec![imported.schema.clone()],
+            };
+            for s in imported {
+                if !schemas.iter().any(|other| Arc::ptr_eq(other, &s)) {
+                    schemas.push(s);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    Ok(schemas)
+}
+
+// Binds the values passed with --param to the externs they name in the schema (see
+// Context::bind_extern). Returns the names of any parameters that don't match an extern.
+//
+pub fn bind_params(
+    rt: &runtime::Runtime,
+    ctx: &mut runtime::Context,
+    schema: &compile::SchemaRef,
+    params: &[(String, String)],
+    file: &str,
+) -> Result<Vec<String>, QSError> {
+    let mut unbound = Vec::new();
+    for (name, value) in params {
+        let bound = rt
+            .block_on(ctx.bind_extern(schema, name, value))
+            .context(RuntimeSnafu {
+                file: file.to_string(),
+            })?;
+        if !bound {
+            unbound.push(name.to_string());
+        }
+    }
+
+    Ok(unbound)
+}
@@ -12,12 +12,22 @@ use queryscript::runtime;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-pub fn run(rt: &runtime::Runtime, engine_type: queryscript::runtime::SQLEngineType) {
+pub fn run(
+    rt: &runtime::Runtime,
+    engine_type: queryscript::runtime::SQLEngineType,
+    search_path: Vec<String>,
+    params: Vec<(String, String)>,
+) {
     let cwd = std::env::current_dir()
         .expect("current working directory")
         .display()
         .to_string();
-    let repl_compiler = compile::Compiler::new().unwrap();
+    let repl_compiler = compile::Compiler::new_with_config(compile::CompilerConfig {
+        search_path,
+        engine: engine_type,
+        ..Default::default()
+    })
+    .unwrap();
     let file = "<repl>".to_string();
     let repl_schema = schema::Schema::new(file.clone(), Some(cwd));
     let curr_buffer = Rc::new(RefCell::new(String::new()));
@@ -87,6 +97,7 @@ pub fn run(rt: &runtime::Runtime, engine_type: queryscript::runtime::SQLEngineTy
                     repl_schema.clone(),
                     &*curr_buffer.borrow(),
                     engine_type,
+                    &params,
                 );
                 match result {
                     Ok(RunCommandResult::Done) => {
@@ -140,6 +151,7 @@ fn run_command(
     repl_schema: schema::SchemaRef,
     cmd: &str,
     engine_type: queryscript::runtime::SQLEngineType,
+    params: &[(String, String)],
 ) -> Result<RunCommandResult, QSError> {
     let file = "<repl>";
     let (tokens, eof) = parser::tokenize(file, &cmd)?;
@@ -170,7 +182,11 @@ fn run_command(
             };
 
             if let Some(compiled) = compiled {
-                let ctx = queryscript::runtime::Context::new(&repl_schema, engine_type);
+                // Parameters are bound as their placeholders appear in the session, so it's
+                // fine for some of them to not match an extern (yet).
+                //
+                let mut ctx = queryscript::runtime::Context::new(&repl_schema, engine_type);
+                crate::bind_params(rt, &mut ctx, &repl_schema, params, file)?;
                 let expr = compiled.to_runtime_type().context(RuntimeSnafu {
                     file: file.to_string(),
                 })?;
@@ -0,0 +1,57 @@
+use std::process::Command;
+
+// Runs `qs test` against tests/qs/tests.qs, which imports tests/qs/lib.qs. The users fixture has a
+// duplicate id, so one of the tests fails, and another one can't be run at all.
+//
+#[test]
+fn test_command_reports_results() {
+    let output = Command::new(env!("CARGO_BIN_EXE_qs"))
+        .args(["test", "tests/qs/tests.qs"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("Failed to run qs");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines = stdout.lines().collect::<Vec<_>>();
+
+    assert!(!output.status.success(), "qs test should fail:\n{}", stdout);
+
+    let result = |name: &str| {
+        lines
+            .iter()
+            .find(|line| line.ends_with(name))
+            .and_then(|line| line.split_whitespace().next())
+            .unwrap_or_else(|| panic!("No result for {} in:\n{}", name, stdout))
+    };
+    assert_eq!(result("test \"no duplicate users\""), "FAIL");
+    assert_eq!(result("test \"names are numbers\""), "ERROR");
+    assert_eq!(result("assert"), "PASS");
+
+    // Tests of imported schemas are run too.
+    assert_eq!(result("test \"users have names\""), "PASS");
+
+    assert!(
+        lines.contains(&"2 passed, 2 failed"),
+        "Missing summary in:\n{}",
+        stdout
+    );
+}
+
+// `qs run` runs a file just like passing it directly, which is unambiguous even for a file named
+// like a subcommand.
+//
+#[test]
+fn run_command_runs_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_qs"))
+        .args(["run", "tests/qs/lib.qs"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("Failed to run qs");
+
+    assert!(
+        output.status.success(),
+        "qs run failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -25,7 +25,7 @@ use queryscript::{
     compile::{
         autocomplete::{loc_to_pos, pos_to_loc, AutoCompleter},
         error::CompileError,
-        schema::{CRef, ExprEntry, MFnType, MType, SType},
+        schema::{CRef, Doc, ExprEntry, Ident, MFnType, MType, SType},
         Compiler, Schema, SchemaRef,
     },
     parser::{error::PrettyError, parse_schema},
@@ -59,6 +59,7 @@ pub struct Symbol {
     pub type_: CRef<SType>,
     pub def: SourceLocation,
     pub public: bool,
+    pub doc: Doc,
     pub references: BTreeSet<SourceLocation>,
 }
 
@@ -356,6 +357,7 @@ impl LanguageServer for Backend {
         let compiler = self.compiler.clone();
         let (suggestion_pos, suggestions) = task::spawn_blocking({
             let line = line.clone();
+            let schema = schema.clone();
             move || -> Result<_> {
                 let compiler = compiler.lock().map_err(log_internal_error)?;
                 let autocompleter = AutoCompleter::new(
@@ -381,6 +383,8 @@ impl LanguageServer for Backend {
         let suggestion_loc =
             pos_to_loc(text.as_str(), start_pos + suggestion_pos).map_err(log_internal_error)?;
 
+        let schema = schema.read().map_err(log_internal_error)?;
+
         Ok(Some(CompletionResponse::List(CompletionList {
             is_incomplete: true,
             items: suggestions
@@ -394,6 +398,12 @@ impl LanguageServer for Backend {
                         },
                         new_text: s.to_string(),
                     })),
+                    documentation: suggestion_doc(&schema, s).map(|doc| {
+                        Documentation::MarkupContent(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value: doc,
+                        })
+                    }),
                     ..Default::default()
                 })
                 .collect(),
@@ -426,6 +436,9 @@ impl LanguageServer for Backend {
                     value: formatted,
                 }));
             }
+            if !symbol.doc.is_empty() {
+                contents.push(MarkedString::String(format_doc(&symbol.doc)));
+            }
 
             Ok(symbol.name.location().range().map(|range| Hover {
                 contents: HoverContents::Array(contents),
@@ -629,6 +642,38 @@ fn format_symbol(symbol: &Symbol) -> Result<String> {
     Ok(parts.join(""))
 }
 
+fn format_doc(doc: &Doc) -> String {
+    let mut parts = Vec::new();
+    if let Some(text) = &doc.text {
+        parts.push(text.clone());
+    }
+    for (name, text) in doc.fields.iter() {
+        parts.push(format!("- `{}`: {}", name, text));
+    }
+    parts.join("\n\n")
+}
+
+// Finds the doc comment of a declaration that a completion suggestion refers to, if any.
+//
+fn suggestion_doc(schema: &Schema, suggestion: &str) -> Option<String> {
+    let name: Ident = suggestion.to_string().into();
+    let doc = if let Some(decl) = schema.expr_decls.get(&name) {
+        &decl.doc
+    } else if let Some(decl) = schema.type_decls.get(&name) {
+        &decl.doc
+    } else if let Some(decl) = schema.schema_decls.get(&name) {
+        &decl.doc
+    } else {
+        return None;
+    };
+
+    if doc.is_empty() {
+        None
+    } else {
+        Some(format_doc(doc))
+    }
+}
+
 fn is_runnable_decl(e: &ExprEntry) -> Result<bool> {
     Ok(match e.to_runtime_type() {
         Ok(e) => match *(e.type_.read().map_err(log_internal_error)?) {
@@ -708,6 +753,7 @@ impl compile::OnSymbol for SymbolRecorder {
         type_: CRef<SType>,
         def: SourceLocation,
         public: bool,
+        doc: Doc,
     ) -> compile::Result<()> {
         let file = name.location().file();
         if let Some(file) = file {
@@ -722,6 +768,7 @@ impl compile::OnSymbol for SymbolRecorder {
                 type_,
                 def,
                 public,
+                doc,
                 references: BTreeSet::new(),
             };
             match self.symbols.entry(uri) {
@@ -1,5 +1,6 @@
 use colored::*;
 pub use sqlparser::ast as sqlast;
+use std::collections::BTreeMap;
 
 pub use sqlparser::{location::Range, tokenizer::Location};
 
@@ -187,6 +188,8 @@ impl ToPath for sqlast::ObjectName {
 pub struct NameAndType {
     pub name: Located<Ident>,
     pub def: Type,
+    pub nullable: bool,
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -199,6 +202,8 @@ pub struct NameAndExpr {
 pub enum StructEntry {
     NameAndType(NameAndType),
     Include(Path),
+    // `..`, which allows any other fields (only in generic bounds)
+    Rest,
 }
 
 #[derive(Clone, Debug)]
@@ -211,6 +216,19 @@ pub enum TypeBody {
         excluded: Vec<Located<Ident>>,
     },
     Generic(Path, Vec<Type>),
+    // A builtin type with arguments, e.g. `decimal(10, 2)` or `timestamp(ms, 'UTC')`
+    Parameterized(Path, Vec<Located<String>>),
+    Union(Vec<NameAndType>),
+    Dictionary {
+        key: Option<Box<Type>>,
+        value: Box<Type>,
+    },
+    Enum(Vec<String>),
+    // A function type, e.g. `fn(bigint, string) -> double`
+    Fn {
+        args: Vec<Type>,
+        ret: Box<Type>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -224,12 +242,55 @@ pub struct Type {
 pub struct FnArg {
     pub name: Located<Ident>,
     pub type_: Type,
+    pub nullable: bool,
+    pub default: Option<Expr>,
 }
 
 #[derive(Clone, Debug)]
 pub enum ExprBody {
     SQLQuery(sqlast::Query),
     SQLExpr(sqlast::Expr),
+    ModelQuery(ModelQuery),
+}
+
+// A measure or dimension of a model, e.g. `measure revenue = sum(amount)`. Unqualified names in
+// the expression refer to the columns of the model's relation if it has them, and otherwise to
+// declarations in the schema (e.g. a `let` or an `extern`).
+//
+#[derive(Clone, Debug)]
+pub struct ModelField {
+    pub name: Located<Ident>,
+    pub expr: sqlast::Expr,
+}
+
+// A join from one model to another, e.g. `join users on user_id = users.id`. Joins are
+// many-to-one, i.e. each row of the joining model matches at most one row of the joined one.
+//
+#[derive(Clone, Debug)]
+pub struct ModelJoin {
+    pub model: Located<Ident>,
+    pub on: sqlast::Expr,
+}
+
+#[derive(Clone, Debug)]
+pub struct Model {
+    pub name: Located<Ident>,
+    // The relation the model is over, e.g. `model orders from paid_orders { ... }`. By default,
+    // this is the relation with the same name as the model.
+    pub relation: Option<sqlast::TableFactor>,
+    pub joins: Vec<ModelJoin>,
+    pub measures: Vec<ModelField>,
+    pub dimensions: Vec<ModelField>,
+}
+
+// A query against a model, e.g. `query orders { measures revenue; dimensions day, users.city }`,
+// which groups the measures by the dimensions. Dimensions may be taken from joined models.
+//
+#[derive(Clone, Debug)]
+pub struct ModelQuery {
+    pub model: Located<Ident>,
+    pub measures: Vec<Path>,
+    pub dimensions: Vec<Path>,
 }
 
 #[derive(Clone, Debug)]
@@ -240,11 +301,27 @@ pub struct Expr {
     pub is_unsafe: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct ImportItem {
+    pub path: Path,
+    pub alias: Option<Located<Ident>>,
+}
+
+impl ImportItem {
+    // The name the item is declared as in the importing schema
+    pub fn name(&self) -> &Located<Ident> {
+        match &self.alias {
+            Some(alias) => alias,
+            None => self.path.last().unwrap(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ImportList {
     None,
     Star,
-    Items(Vec<Path>),
+    Items(Vec<ImportItem>),
 }
 
 #[derive(Clone, Debug)]
@@ -263,14 +340,22 @@ pub enum StmtBody {
         path: Path,
         list: ImportList,
         args: Option<Vec<NameAndExpr>>,
+        alias: Option<Located<Ident>>,
+    },
+    TypeDef {
+        name: Located<Ident>,
+        generics: Vec<Located<Ident>>,
+        def: Type,
     },
-    TypeDef(NameAndType),
     FnDef {
         name: Located<Ident>,
         generics: Vec<Located<Ident>>,
+        // Record types that generic arguments must conform to, e.g. `R: {id bigint, ..}`
+        bounds: BTreeMap<Ident, Type>,
         args: Vec<FnArg>,
         ret: Option<Type>,
         body: FnBody,
+        aggregate: bool,
     },
     Let {
         name: Located<Ident>,
@@ -281,11 +366,22 @@ pub enum StmtBody {
         name: Located<Ident>,
         type_: Type,
     },
+    // Data tests, i.e. `test "name" { <query> } expect empty` and `assert <expr>`. These are
+    // compiled with the rest of the schema but only run under `qs test`.
+    //
+    Test {
+        name: Located<String>,
+        body: Expr,
+    },
+    Assert(Expr),
+    Model(Model),
 }
 
 #[derive(Clone, Debug)]
 pub struct Stmt {
     pub export: bool,
+    // The `---` doc comment preceding the statement, if any
+    pub doc: Option<String>,
     pub body: StmtBody,
     pub start: Location,
     pub end: Location,
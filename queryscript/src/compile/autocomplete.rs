@@ -12,7 +12,6 @@ use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fmt;
 use std::fs::read_dir;
-use std::path::Path;
 use std::rc::Rc;
 
 #[derive(Clone, Debug)]
@@ -137,26 +136,33 @@ fn get_imported_decls<E: schema::Entry>(
 }
 
 fn get_schema_paths(
+    compiler: compile::Compiler,
     schema: schema::Ref<schema::Schema>,
     path: &Vec<ast::Ident>,
 ) -> Result<Vec<String>> {
     if let Some(folder) = schema.read()?.folder.clone() {
-        let mut folder = Path::new(&folder).to_path_buf();
-        folder.extend(path.iter().map(|s| s.to_string()));
-        let files = read_dir(folder)?;
         let mut ret = Vec::new();
-        for f in files {
-            if let Ok(f) = f {
-                let file = f.path();
-                let extension = file.extension().and_then(OsStr::to_str).unwrap_or("");
-                if schema::SCHEMA_EXTENSIONS.contains(&extension) {
-                    if let Some(fp) = file.file_stem().and_then(OsStr::to_str) {
-                        ret.push(fp.to_string());
-                    }
-                }
-                if file.is_dir() {
-                    if let Some(fp) = file.file_name().and_then(OsStr::to_str) {
-                        ret.push(fp.to_string());
+        for mut dir in compiler.search_path(&folder)? {
+            dir.extend(path.iter().map(|s| s.to_string()));
+            let files = match read_dir(dir) {
+                Ok(files) => files,
+                Err(_) => continue,
+            };
+            for f in files {
+                if let Ok(f) = f {
+                    let file = f.path();
+                    let extension = file.extension().and_then(OsStr::to_str).unwrap_or("");
+                    let name = if schema::SCHEMA_EXTENSIONS.contains(&extension) {
+                        file.file_stem().and_then(OsStr::to_str)
+                    } else if file.is_dir() {
+                        file.file_name().and_then(OsStr::to_str)
+                    } else {
+                        None
+                    };
+                    if let Some(name) = name {
+                        if !ret.iter().any(|r| r == name) {
+                            ret.push(name.to_string());
+                        }
                     }
                 }
             }
@@ -300,7 +306,11 @@ impl AutoCompleter {
             .get(&parser::AUTOCOMPLETE_SCHEMA)
             .map(parse_longest_path)
             .map_or(Vec::new(), |path| {
-                if let Ok(choices) = get_schema_paths(self.schema.clone(), &path.to_idents()) {
+                if let Ok(choices) = get_schema_paths(
+                    self.compiler.clone(),
+                    self.schema.clone(),
+                    &path.to_idents(),
+                ) {
                     return choices.into_iter().map(|s| s.into()).collect();
                 }
                 Vec::new()
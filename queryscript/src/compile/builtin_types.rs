@@ -3,54 +3,125 @@ use std::collections::BTreeMap;
 
 use crate::ast::{Ident, SourceLocation};
 use crate::compile::compile::{Compiler, CompilerConfig};
+use crate::compile::error::{CompileError, Result};
 use crate::compile::inference::mkcref;
-use crate::compile::schema::{Decl, Located, MType, Ref, Schema, TypeEntry};
+use crate::compile::schema::{Decl, Doc, Located, MType, Ref, Schema, TypeEntry};
+use crate::runtime::SQLEngineType;
 use crate::types::{AtomicType, TimeUnit};
 
 pub use crate::compile::generics::GLOBAL_GENERICS;
 
-// TODO: Add support for types with arguments
-type BuiltinType = (&'static str, AtomicType);
-const BUILTIN_TYPES: &'static [BuiltinType] = &[
-    //
-    // Numbers
-    //
-    ("number", AtomicType::Float64),
-    ("tinyint", AtomicType::Int8),
-    ("smallint", AtomicType::Int16),
-    ("int", AtomicType::Int32),
-    ("bigint", AtomicType::Int64),
-    ("hugeint", AtomicType::Decimal128(38, 0)), // XXX This is a hack because DuckDB's sum returns this
-    ("float", AtomicType::Float32),
-    ("double", AtomicType::Float64),
-    //
-    // Strings
-    //
-    ("string", AtomicType::Utf8),
-    ("text", AtomicType::Utf8),
-    ("varchar", AtomicType::Utf8),
-    //
-    // Date/Time:
-    // - The parser in rust arrow expects Date32 to be a date (no time) and
-    //   Date64 to be a date and time (w/ optional timezone)
-    //   https://github.com/apache/arrow-rs/blob/27.0.0/arrow-cast/src/parse.rs#L224
-    // - The time types are defaulted to microsecond precision, but we should make
-    //   this configurable.
-    // - The timestamp has no timezone, but we should make this configurable.
-    //
-    ("date", AtomicType::Date32),
-    ("time", AtomicType::Time64(TimeUnit::Microsecond)),
-    ("datetime", AtomicType::Timestamp(TimeUnit::Second, None)),
-    (
-        "timestamp",
-        AtomicType::Timestamp(TimeUnit::Microsecond, None),
-    ),
-    //
-    // Other
-    //
-    ("bool", AtomicType::Boolean),
-    ("null", AtomicType::Null),
-];
+// Resolves a builtin type that takes arguments, e.g. `decimal(10, 2)`, `timestamp(ms, 'UTC')`,
+// or `time(ns)`. Written without arguments, the types in BUILTIN_TYPES use their default
+// parameters instead. Decimals can't be more precise than `engine` can store.
+//
+pub fn parameterized_type(
+    engine: SQLEngineType,
+    loc: &SourceLocation,
+    name: &str,
+    args: &Vec<Located<String>>,
+) -> Result<AtomicType> {
+    let arity = |min: usize, max: usize| {
+        if args.len() < min || args.len() > max {
+            Err(CompileError::type_args(
+                loc.clone(),
+                name,
+                &format!(
+                    "expected {} to {} arguments, found {}",
+                    min,
+                    max,
+                    args.len()
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    };
+    let number = |arg: &Located<String>| {
+        arg.get().parse::<u8>().map_err(|_| {
+            CompileError::type_args(
+                arg.location().clone(),
+                name,
+                &format!("expected a number, found {}", arg.get()),
+            )
+        })
+    };
+    let time_unit = |arg: &Located<String>| match arg.get().to_lowercase().as_str() {
+        "s" | "second" | "seconds" => Ok(TimeUnit::Second),
+        "ms" | "millisecond" | "milliseconds" => Ok(TimeUnit::Millisecond),
+        "us" | "microsecond" | "microseconds" => Ok(TimeUnit::Microsecond),
+        "ns" | "nanosecond" | "nanoseconds" => Ok(TimeUnit::Nanosecond),
+        other => Err(CompileError::type_args(
+            arg.location().clone(),
+            name,
+            &format!("expected a time unit (s, ms, us, or ns), found {}", other),
+        )),
+    };
+
+    match name.to_lowercase().as_str() {
+        "decimal" => {
+            arity(1, 2)?;
+            let precision = number(&args[0])?;
+            let scale = match args.get(1) {
+                Some(scale) => number(scale)?,
+                None => 0,
+            };
+            if precision == 0 || scale > precision {
+                return Err(CompileError::type_args(
+                    loc.clone(),
+                    name,
+                    &format!("invalid precision and scale ({}, {})", precision, scale),
+                ));
+            }
+            let max_precision = engine.max_decimal_precision();
+            if precision > max_precision {
+                return Err(CompileError::type_args(
+                    loc.clone(),
+                    name,
+                    &format!(
+                        "precision {} is larger than the maximum of the {:?} engine ({})",
+                        precision, engine, max_precision
+                    ),
+                ));
+            }
+            match precision {
+                1..=38 => Ok(AtomicType::Decimal128(precision, scale)),
+                _ => Ok(AtomicType::Decimal256(precision, scale)),
+            }
+        }
+        // Strings have no length, so a length would silently not be enforced
+        "string" | "text" | "varchar" => Err(CompileError::type_args(
+            loc.clone(),
+            name,
+            "strings do not have a length",
+        )),
+        "timestamptz" => {
+            arity(1, 1)?;
+            Ok(AtomicType::Timestamp(
+                time_unit(&args[0])?,
+                Some("UTC".to_string()),
+            ))
+        }
+        "timestamp" | "datetime" => {
+            arity(1, 2)?;
+            let unit = time_unit(&args[0])?;
+            let tz = args.get(1).map(|tz| tz.get().clone());
+            Ok(AtomicType::Timestamp(unit, tz))
+        }
+        "time" => {
+            arity(1, 1)?;
+            Ok(match time_unit(&args[0])? {
+                unit @ (TimeUnit::Second | TimeUnit::Millisecond) => AtomicType::Time32(unit),
+                unit => AtomicType::Time64(unit),
+            })
+        }
+        _ => Err(CompileError::type_args(
+            loc.clone(),
+            name,
+            "type does not take arguments",
+        )),
+    }
+}
 
 const BUILTIN_FUNCTIONS: &'static str = "
 fn load<R>(file varchar, format varchar) -> External<[R]> = native;
@@ -58,7 +129,17 @@ fn __native_identity<T>(value T) -> T = native;
 
 -- Functions
 fn abs<R>(value R) -> R = sql;
-fn strptime<R>(value text, fmt string) -> timestamp = sql;
+fn strptime(value text, fmt string) -> timestamp = sql;
+fn strftime(value date, fmt string) -> string = sql;
+fn strftime(value timestamp, fmt string) -> string = sql;
+fn date_trunc(part string, value date) -> timestamp = sql;
+fn date_trunc(part string, value timestamp) -> timestamp = sql;
+fn date_trunc(part string, value timestamptz) -> timestamptz = sql;
+fn date_part(part string, value date) -> bigint = sql;
+fn date_part(part string, value timestamp) -> bigint = sql;
+fn date_part(part string, value timestamptz) -> bigint = sql;
+fn date_diff(part string, startdate date, enddate date) -> bigint = sql;
+fn date_diff(part string, startdate timestamp, enddate timestamp) -> bigint = sql;
 
 -- Aggs
 fn min<R>(value R) -> R = sql;
@@ -71,7 +152,54 @@ fn avg<R>(value R) -> double = sql;
 fn row_number<R>() -> bigint = sql;
 ";
 
+type BuiltinType = (&'static str, AtomicType);
+
 lazy_static! {
+    static ref BUILTIN_TYPES: Vec<BuiltinType> = vec![
+        //
+        // Numbers
+        //
+        ("number", AtomicType::Float64),
+        ("tinyint", AtomicType::Int8),
+        ("smallint", AtomicType::Int16),
+        ("int", AtomicType::Int32),
+        ("bigint", AtomicType::Int64),
+        ("hugeint", AtomicType::Decimal128(38, 0)), // XXX This is a hack because DuckDB's sum returns this
+        ("float", AtomicType::Float32),
+        ("double", AtomicType::Float64),
+        //
+        // Strings
+        //
+        ("string", AtomicType::Utf8),
+        ("text", AtomicType::Utf8),
+        ("varchar", AtomicType::Utf8),
+        //
+        // Date/Time:
+        // - The parser in rust arrow expects Date32 to be a date (no time) and
+        //   Date64 to be a date and time (w/ optional timezone)
+        //   https://github.com/apache/arrow-rs/blob/27.0.0/arrow-cast/src/parse.rs#L224
+        // - The time types are defaulted to microsecond precision and the timestamp
+        //   has no timezone. Both can be specified as arguments (see parameterized_type).
+        // - A timestamptz is stored as a UTC instant, and is displayed in its timezone.
+        //
+        ("date", AtomicType::Date32),
+        ("time", AtomicType::Time64(TimeUnit::Microsecond)),
+        ("datetime", AtomicType::Timestamp(TimeUnit::Second, None)),
+        (
+            "timestamp",
+            AtomicType::Timestamp(TimeUnit::Microsecond, None),
+        ),
+        (
+            "timestamptz",
+            AtomicType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string())),
+        ),
+        //
+        // Other
+        //
+        ("bool", AtomicType::Boolean),
+        ("null", AtomicType::Null),
+    ];
+
     pub static ref BUILTIN_LOC: SourceLocation = SourceLocation::File("<builtin>".to_string());
     static ref BUILTIN_TYPE_DECLS: Vec<(Ident, Decl<TypeEntry>)> = BUILTIN_TYPES
         .iter()
@@ -86,6 +214,7 @@ lazy_static! {
                     type_.clone(),
                     BUILTIN_LOC.clone()
                 ))),
+                doc: Doc::default(),
             },
         ))
         .collect();
@@ -1,5 +1,5 @@
 use arrow::datatypes::DataType as ArrowDataType;
-use sqlparser::ast::BinaryOperator;
+use sqlparser::ast::{BinaryOperator, UnaryOperator};
 
 use crate::types::{AtomicType, TimeUnit, Type, DECIMAL128_MAX_PRECISION, DECIMAL128_MAX_SCALE};
 
@@ -21,10 +21,12 @@ macro_rules! must_atomic {
     };
 }
 
+#[derive(Clone)]
 pub enum CoerceOp {
     Binary(BinaryOperator),
     Like,
     IsDistinctFrom,
+    ListContains,
 }
 
 impl From<BinaryOperator> for CoerceOp {
@@ -61,19 +63,20 @@ pub fn coerce_types(lhs_type: &Type, op: &CoerceOp, rhs_type: &Type) -> Option<T
     match op {
         CoerceOp::Binary(op) => match op {
             BinaryOperator::BitwiseAnd
-            | BinaryOperator::Xor
             | BinaryOperator::BitwiseOr
             | BinaryOperator::PGBitwiseXor
             | BinaryOperator::BitwiseXor
             | BinaryOperator::PGBitwiseShiftRight
             | BinaryOperator::PGBitwiseShiftLeft => bitwise_coercion(lhs_type, rhs_type),
-            BinaryOperator::And | BinaryOperator::Or => match (lhs_type, rhs_type) {
-                // logical binary boolean operators can only be evaluated in bools
-                (Type::Atom(AtomicType::Boolean), Type::Atom(AtomicType::Boolean)) => {
-                    Some(Type::Atom(AtomicType::Boolean))
+            BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor => {
+                match (lhs_type, rhs_type) {
+                    // logical binary boolean operators can only be evaluated in bools
+                    (Type::Atom(AtomicType::Boolean), Type::Atom(AtomicType::Boolean)) => {
+                        Some(Type::Atom(AtomicType::Boolean))
+                    }
+                    _ => None,
                 }
-                _ => None,
-            },
+            }
             // logical comparison operators have their own rules, and always return a boolean
             BinaryOperator::Eq
             | BinaryOperator::NotEq
@@ -114,6 +117,39 @@ pub fn coerce_types(lhs_type: &Type, op: &CoerceOp, rhs_type: &Type) -> Option<T
             like_coercion(lhs_type, rhs_type)
         }
         CoerceOp::IsDistinctFrom => eq_coercion(lhs_type, rhs_type),
+        // "@>" checks whether every element of the rhs list is in the lhs list
+        CoerceOp::ListContains => list_contains_coercion(lhs_type, rhs_type),
+    }
+}
+
+/// Coercion rules for unary operators. Returns the output type of applying
+/// `op` to an argument of `type_`.
+///
+/// Returns None if the operator cannot be applied to `type_`.
+pub fn unary_coerce_type(op: &UnaryOperator, type_: &Type) -> Option<Type> {
+    use AtomicType::*;
+    let atom = must_atomic!(type_);
+    match op {
+        UnaryOperator::Plus | UnaryOperator::Minus | UnaryOperator::PGAbs
+            if is_numeric(atom) || matches!(atom, Interval(_) | Null) =>
+        {
+            Some(type_.clone())
+        }
+        UnaryOperator::Not if matches!(atom, Boolean | Null) => Some(Type::Atom(Boolean)),
+        // "~" flips the bits of an integer, and preserves its type
+        UnaryOperator::PGBitwiseNot if is_integer(atom) || *atom == Null => Some(type_.clone()),
+        // "!" (and "!!") is the factorial, which engines compute over 128 bit integers
+        UnaryOperator::PGPostfixFactorial | UnaryOperator::PGPrefixFactorial
+            if is_integer(atom) || *atom == Null =>
+        {
+            Some(Type::Atom(Decimal128(38, 0)))
+        }
+        UnaryOperator::PGSquareRoot | UnaryOperator::PGCubeRoot
+            if is_numeric(atom) || *atom == Null =>
+        {
+            Some(Type::Atom(Float64))
+        }
+        _ => None,
     }
 }
 
@@ -147,6 +183,15 @@ pub fn is_numeric(dt: &AtomicType) -> bool {
     is_signed_numeric(dt) || matches!(dt, UInt8 | UInt16 | UInt32 | UInt64)
 }
 
+/// Determine if a Type is a (signed or unsigned) integer or not
+pub fn is_integer(dt: &AtomicType) -> bool {
+    use AtomicType::*;
+    matches!(
+        dt,
+        Int8 | Int16 | Int32 | Int64 | UInt8 | UInt16 | UInt32 | UInt64
+    )
+}
+
 /// Determine if a Type is signed numeric or not
 pub fn is_signed_numeric(dt: &AtomicType) -> bool {
     use AtomicType::*;
@@ -172,9 +217,11 @@ pub fn comparison_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
         return Some(lhs_type.clone());
     }
     comparison_binary_numeric_coercion(lhs_type, rhs_type)
-        // .or_else(|| dictionary_coercion(lhs_type, rhs_type, true)) // TODO: QueryScript does not support dictionaries
+        .or_else(|| dictionary_coercion(lhs_type, rhs_type, true))
+        .or_else(|| union_coercion(lhs_type, rhs_type))
         .or_else(|| temporal_coercion(lhs_type, rhs_type))
         .or_else(|| string_coercion(lhs_type, rhs_type))
+        .or_else(|| enum_coercion(lhs_type, rhs_type))
         .or_else(|| null_coercion(lhs_type, rhs_type))
         .or_else(|| string_numeric_coercion(lhs_type, rhs_type))
 }
@@ -484,11 +531,87 @@ fn string_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
     .map(Type::Atom)
 }
 
+/// Coercion rules for list containment: both sides must be lists whose elements
+/// can be compared, and they are coerced to a list of the common element type.
+fn list_contains_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
+    match (lhs_type, rhs_type) {
+        (Type::List(lhs_elem), Type::List(rhs_elem)) => {
+            comparison_coercion(lhs_elem, rhs_elem).map(|t| Type::List(Box::new(t)))
+        }
+        _ => null_coercion(lhs_type, rhs_type),
+    }
+}
+
+/// Coercion rules for Dictionaries: the type that both lhs and rhs
+/// can be casted to for the purpose of a computation.
+///
+/// It would likely be preferable to cast primitive values to
+/// dictionaries, and thus avoid unpacking dictionary values, but
+/// this is not always possible (e.g. for `like`, which only operates
+/// on strings), so `preserve_dictionaries` controls whether the
+/// result may be a dictionary.
+fn dictionary_coercion(
+    lhs_type: &Type,
+    rhs_type: &Type,
+    preserve_dictionaries: bool,
+) -> Option<Type> {
+    use AtomicType::*;
+    match (must_atomic!(lhs_type), must_atomic!(rhs_type)) {
+        (Dictionary(_, lhs_value_type), Dictionary(_, rhs_value_type)) => comparison_coercion(
+            &Type::Atom(lhs_value_type.as_ref().clone()),
+            &Type::Atom(rhs_value_type.as_ref().clone()),
+        ),
+        (d @ Dictionary(_, value_type), other_type)
+        | (other_type, d @ Dictionary(_, value_type))
+            if preserve_dictionaries && value_type.as_ref() == other_type =>
+        {
+            Some(Type::Atom(d.clone()))
+        }
+        (Dictionary(_, value_type), _) => {
+            comparison_coercion(&Type::Atom(value_type.as_ref().clone()), rhs_type)
+        }
+        (_, Dictionary(_, value_type)) => {
+            comparison_coercion(lhs_type, &Type::Atom(value_type.as_ref().clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Coercion rules for Unions: a union can be compared to a value of one
+/// of its members' types, which the engine converts to the union (it is
+/// not casted explicitly, since unions can't be the target of a cast).
+fn union_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
+    match (lhs_type, rhs_type) {
+        (u @ Type::Union(fields), other_type) | (other_type, u @ Type::Union(fields))
+            if fields.iter().any(|f| &f.type_ == other_type) =>
+        {
+            Some(u.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Coercion rules for Enums: an enum can be compared to a string, in which
+/// case both are compared as strings. Enums with different members cannot
+/// be compared, unless the members of one of them are unknown.
+fn enum_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
+    use AtomicType::*;
+    match (must_atomic!(lhs_type), must_atomic!(rhs_type)) {
+        (Enum(_), s @ (Utf8 | LargeUtf8)) | (s @ (Utf8 | LargeUtf8), Enum(_)) => Some(s.clone()),
+        (Enum(members), Enum(unknown)) | (Enum(unknown), Enum(members)) if unknown.is_empty() => {
+            Some(Enum(members.clone()))
+        }
+        _ => None,
+    }
+    .map(Type::Atom)
+}
+
 /// coercion rules for like operations.
 /// This is a union of string coercion rules and dictionary coercion rules
 fn like_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
     string_coercion(lhs_type, rhs_type)
-        // .or_else(|| dictionary_coercion(lhs_type, rhs_type, false)) // TODO: Support dictionary types
+        .or_else(|| dictionary_coercion(lhs_type, rhs_type, false))
+        .or_else(|| enum_coercion(lhs_type, rhs_type))
         .or_else(|| null_coercion(lhs_type, rhs_type))
 }
 
@@ -503,18 +626,16 @@ fn temporal_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
         (Date32, Utf8) => Some(Date32),
         (Utf8, Date64) => Some(Date64),
         (Date64, Utf8) => Some(Date64),
+        (Utf8, Timestamp(unit, tz)) | (Timestamp(unit, tz), Utf8) => {
+            Some(Timestamp(unit.clone(), tz.clone()))
+        }
         (Timestamp(lhs_unit, lhs_tz), Timestamp(rhs_unit, rhs_tz)) => {
             let tz = match (lhs_tz, rhs_tz) {
-                // can't cast across timezones
-                (Some(lhs_tz), Some(rhs_tz)) => {
-                    if lhs_tz != rhs_tz {
-                        return None;
-                    } else {
-                        Some(lhs_tz.clone())
-                    }
-                }
-                (Some(lhs_tz), None) => Some(lhs_tz.clone()),
-                (None, Some(rhs_tz)) => Some(rhs_tz.clone()),
+                // zoned timestamps are stored as UTC instants, so they can be compared across
+                // timezones, but there is no single zone to report them in other than UTC
+                (Some(lhs_tz), Some(rhs_tz)) if lhs_tz != rhs_tz => Some("UTC".to_string()),
+                // a naive timestamp is interpreted in the timezone of the zoned one
+                (Some(tz), _) | (None, Some(tz)) => Some(tz.clone()),
                 (None, None) => None,
             };
 
@@ -586,7 +707,9 @@ fn eq_coercion(lhs_type: &Type, rhs_type: &Type) -> Option<Type> {
         return Some(lhs_type.clone());
     }
     numerical_coercion(lhs_type, rhs_type)
-        // .or_else(|| dictionary_coercion(lhs_type, rhs_type, true)) // TODO: Support dictionaries
+        .or_else(|| dictionary_coercion(lhs_type, rhs_type, true))
+        .or_else(|| union_coercion(lhs_type, rhs_type))
+        .or_else(|| enum_coercion(lhs_type, rhs_type))
         .or_else(|| temporal_coercion(lhs_type, rhs_type))
         .or_else(|| null_coercion(lhs_type, rhs_type))
 }
@@ -741,46 +864,103 @@ mod tests {
         assert_eq!(DataType::Decimal128(11, 4), result.unwrap());
     }
 
-    /*
     #[test]
     fn test_dictionary_type_coercion() {
         use DataType::*;
+        let atom = |t: DataType| Type::Atom(t);
 
-        let lhs_type = Dictionary(Box::new(Int8), Box::new(Int32));
-        let rhs_type = Dictionary(Box::new(Int8), Box::new(Int16));
-        assert_eq!(dictionary_coercion(&lhs_type, &rhs_type, true), Some(Int32));
+        let lhs_type = atom(Dictionary(Box::new(Int8), Box::new(Int32)));
+        let rhs_type = atom(Dictionary(Box::new(Int8), Box::new(Int16)));
+        assert_eq!(
+            dictionary_coercion(&lhs_type, &rhs_type, true),
+            Some(atom(Int32))
+        );
         assert_eq!(
             dictionary_coercion(&lhs_type, &rhs_type, false),
-            Some(Int32)
+            Some(atom(Int32))
         );
 
         // Since we can coerce values of Int16 to Utf8 can support this
-        let lhs_type = Dictionary(Box::new(Int8), Box::new(Utf8));
-        let rhs_type = Dictionary(Box::new(Int8), Box::new(Int16));
-        assert_eq!(dictionary_coercion(&lhs_type, &rhs_type, true), Some(Utf8));
+        let lhs_type = atom(Dictionary(Box::new(Int8), Box::new(Utf8)));
+        let rhs_type = atom(Dictionary(Box::new(Int8), Box::new(Int16)));
+        assert_eq!(
+            dictionary_coercion(&lhs_type, &rhs_type, true),
+            Some(atom(Utf8))
+        );
 
         // Can not coerce values of Binary to int,  cannot support this
-        let lhs_type = Dictionary(Box::new(Int8), Box::new(Utf8));
-        let rhs_type = Dictionary(Box::new(Int8), Box::new(Binary));
+        let lhs_type = atom(Dictionary(Box::new(Int8), Box::new(Utf8)));
+        let rhs_type = atom(Dictionary(Box::new(Int8), Box::new(Binary)));
         assert_eq!(dictionary_coercion(&lhs_type, &rhs_type, true), None);
 
-        let lhs_type = Dictionary(Box::new(Int8), Box::new(Utf8));
-        let rhs_type = Utf8;
-        assert_eq!(dictionary_coercion(&lhs_type, &rhs_type, false), Some(Utf8));
+        let lhs_type = atom(Dictionary(Box::new(Int8), Box::new(Utf8)));
+        let rhs_type = atom(Utf8);
+        assert_eq!(
+            dictionary_coercion(&lhs_type, &rhs_type, false),
+            Some(atom(Utf8))
+        );
         assert_eq!(
             dictionary_coercion(&lhs_type, &rhs_type, true),
             Some(lhs_type.clone())
         );
 
-        let lhs_type = Utf8;
-        let rhs_type = Dictionary(Box::new(Int8), Box::new(Utf8));
-        assert_eq!(dictionary_coercion(&lhs_type, &rhs_type, false), Some(Utf8));
+        let lhs_type = atom(Utf8);
+        let rhs_type = atom(Dictionary(Box::new(Int8), Box::new(Utf8)));
+        assert_eq!(
+            dictionary_coercion(&lhs_type, &rhs_type, false),
+            Some(atom(Utf8))
+        );
         assert_eq!(
             dictionary_coercion(&lhs_type, &rhs_type, true),
             Some(rhs_type.clone())
         );
     }
-    */
+
+    #[test]
+    fn test_enum_type_coercion() {
+        let enum_type = Type::Atom(DataType::Enum(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(
+            comparison_coercion(&enum_type, &Type::Atom(DataType::Utf8)),
+            Some(Type::Atom(DataType::Utf8))
+        );
+        assert_eq!(
+            comparison_coercion(&enum_type, &enum_type),
+            Some(enum_type.clone())
+        );
+        assert_eq!(
+            comparison_coercion(
+                &enum_type,
+                &Type::Atom(DataType::Enum(vec!["a".to_string()]))
+            ),
+            None
+        );
+        assert_eq!(
+            comparison_coercion(&enum_type, &Type::Atom(DataType::Enum(Vec::new()))),
+            Some(enum_type.clone())
+        );
+    }
+
+    #[test]
+    fn test_union_type_coercion() {
+        use crate::types::Field;
+        let union_type = Type::Union(vec![
+            Field::new_nullable("a".into(), Type::Atom(DataType::Int64)),
+            Field::new_nullable("b".into(), Type::Atom(DataType::Utf8)),
+        ]);
+
+        assert_eq!(
+            comparison_coercion(&union_type, &Type::Atom(DataType::Utf8)),
+            Some(union_type.clone())
+        );
+        assert_eq!(
+            eq_coercion(&Type::Atom(DataType::Int64), &union_type),
+            Some(union_type.clone())
+        );
+        assert_eq!(
+            comparison_coercion(&union_type, &Type::Atom(DataType::Boolean)),
+            None
+        );
+    }
 
     #[allow(unused)]
     macro_rules! test_coercion_binary_rule {
@@ -834,6 +1014,18 @@ mod tests {
             CoerceOp::Binary(Operator::BitwiseAnd),
             DataType::Int64
         );
+        test_coercion_binary_rule!(
+            DataType::Utf8,
+            DataType::Int64,
+            CoerceOp::Binary(Operator::StringConcat),
+            DataType::Utf8
+        );
+        test_coercion_binary_rule!(
+            DataType::Int32,
+            DataType::Float64,
+            CoerceOp::IsDistinctFrom,
+            DataType::Float64
+        );
         Ok(())
     }
 
@@ -986,11 +1178,76 @@ mod tests {
             Operator::GtEq,
             DataType::Decimal128(15, 3)
         );
+        // timestamp
+        test_coercion_binary_rule!(
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string())),
+            Operator::Lt,
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string()))
+        );
+        test_coercion_binary_rule!(
+            DataType::Timestamp(TimeUnit::Microsecond, Some("America/New_York".to_string())),
+            DataType::Timestamp(TimeUnit::Microsecond, Some("Europe/Paris".to_string())),
+            Operator::Eq,
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string()))
+        );
+        test_coercion_binary_rule!(
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".to_string())),
+            DataType::Utf8,
+            Operator::GtEq,
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".to_string()))
+        );
 
         // TODO add other data type
         Ok(())
     }
 
+    #[test]
+    fn test_type_coercion_list_contains() -> Result<()> {
+        let list = |t: DataType| Type::List(Box::new(Type::Atom(t)));
+        assert_eq!(
+            coerce_types_r(
+                &list(DataType::Int32),
+                &CoerceOp::ListContains,
+                &list(DataType::Int64)
+            )?,
+            list(DataType::Int64)
+        );
+        assert_eq!(
+            coerce_types(
+                &list(DataType::Int32),
+                &CoerceOp::ListContains,
+                &Type::Atom(DataType::Int32)
+            ),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unary_coercion() {
+        use sqlparser::ast::UnaryOperator;
+        assert_eq!(
+            unary_coerce_type(&UnaryOperator::PGBitwiseNot, &Type::Atom(DataType::Int16)),
+            Some(Type::Atom(DataType::Int16))
+        );
+        assert_eq!(
+            unary_coerce_type(&UnaryOperator::PGBitwiseNot, &Type::Atom(DataType::Float64)),
+            None
+        );
+        assert_eq!(
+            unary_coerce_type(
+                &UnaryOperator::PGPostfixFactorial,
+                &Type::Atom(DataType::Int32)
+            ),
+            Some(Type::Atom(DataType::Decimal128(38, 0)))
+        );
+        assert_eq!(
+            unary_coerce_type(&UnaryOperator::Not, &Type::Atom(DataType::Utf8)),
+            None
+        );
+    }
+
     #[test]
     fn test_type_coercion_logical_op() -> Result<()> {
         test_coercion_binary_rule!(
@@ -1006,6 +1263,13 @@ mod tests {
             Operator::Or,
             DataType::Boolean
         );
+
+        test_coercion_binary_rule!(
+            DataType::Boolean,
+            DataType::Boolean,
+            Operator::Xor,
+            DataType::Boolean
+        );
         Ok(())
     }
 }
@@ -1,21 +1,28 @@
 use snafu::prelude::*;
+use sqlparser::ast as sqlast;
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList};
 use std::fmt;
 use std::fs;
-use std::path::Path as FilePath;
+use std::path::{Path as FilePath, PathBuf};
 use std::sync::Arc;
 
-use crate::compile::builtin_types::{BUILTIN_LOC, GLOBAL_GENERICS, GLOBAL_SCHEMA};
-use crate::compile::coerce::CoerceOp;
+use crate::compile::builtin_types::{
+    parameterized_type, BUILTIN_LOC, GLOBAL_GENERICS, GLOBAL_SCHEMA,
+};
+use crate::compile::coerce::{is_integer, CoerceOp};
 use crate::compile::error::*;
+use crate::compile::generics::{as_generic, exclude_fields, UserGenericType};
 use crate::compile::inference::*;
+use crate::compile::model::compile_model_query;
 use crate::compile::schema::*;
 use crate::compile::scope::SQLScope;
 use crate::compile::sql::*;
 use crate::compile::unsafe_expr::compile_unsafe_expr;
+use crate::runtime::SQLEngineType;
+use crate::types::AtomicType;
 use crate::{
     ast,
-    ast::{Ident, Located, Range, SourceLocation, ToIdents},
+    ast::{Ident, Located, Pretty, Range, SourceLocation, ToIdents},
 };
 use crate::{c_try, error::MultiResult, parser, parser::parse_schema};
 
@@ -72,6 +79,7 @@ pub struct CompilerData {
     pub next_external_type: usize,
     pub external_types: BinaryHeap<ExternalTypeHandle>,
     pub files: BTreeMap<String, String>,
+    pub import_stack: Vec<Located<String>>,
 }
 
 #[derive(Clone, Debug)]
@@ -93,6 +101,7 @@ pub trait OnSymbol {
         type_: CRef<SType>,
         def: SourceLocation,
         is_public: bool,
+        doc: Doc,
     ) -> Result<()>;
 }
 
@@ -112,6 +121,8 @@ pub trait OnSchema {
 pub struct CompilerConfig {
     pub allow_native: bool,
     pub allow_inlining: bool,
+    pub search_path: Vec<String>,
+    pub engine: SQLEngineType,
     pub on_symbol: Option<Box<dyn OnSymbol + Send + Sync>>,
     pub on_schema: Option<Box<dyn OnSchema + Send + Sync>>,
 }
@@ -121,6 +132,8 @@ impl Default for CompilerConfig {
         CompilerConfig {
             allow_native: false,
             allow_inlining: true,
+            search_path: Vec::new(),
+            engine: SQLEngineType::DuckDB,
             on_symbol: None,
             on_schema: None,
         }
@@ -132,6 +145,8 @@ impl fmt::Debug for CompilerConfig {
         f.debug_struct("CompilerConfig")
             .field("allow_native", &self.allow_native)
             .field("allow_inlining", &self.allow_inlining)
+            .field("search_path", &self.search_path)
+            .field("engine", &self.engine)
             .finish_non_exhaustive()
     }
 }
@@ -190,6 +205,7 @@ impl Compiler {
                 next_external_type: 1,
                 external_types: BinaryHeap::new(),
                 files: BTreeMap::new(),
+                import_stack: Vec::new(),
             }),
             builtins: schema.clone(),
         };
@@ -209,6 +225,50 @@ impl Compiler {
         Ok(self.data.read()?.config.allow_inlining)
     }
 
+    pub fn engine(&self) -> Result<SQLEngineType> {
+        Ok(self.data.read()?.config.engine)
+    }
+
+    // Returns the directories searched for schemas imported by a file in `folder`, in order of
+    // precedence: the folder itself, the configured search path, `QS_PATH`, the directories listed
+    // in the project file, and finally the project's vendored packages.
+    //
+    pub fn search_path(&self, folder: &str) -> Result<Vec<PathBuf>> {
+        let mut dirs = vec![PathBuf::from(folder)];
+        dirs.extend(
+            self.data
+                .read()?
+                .config
+                .search_path
+                .iter()
+                .map(PathBuf::from),
+        );
+
+        if let Some(paths) = std::env::var_os(SEARCH_PATH_ENV) {
+            dirs.extend(std::env::split_paths(&paths));
+        }
+
+        if let Some(root) = find_project_root(FilePath::new(folder)) {
+            let project_file = root.join(PROJECT_FILE);
+            if project_file.is_file() {
+                for line in fs::read_to_string(&project_file)?.lines() {
+                    let line = line.trim();
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+                    dirs.push(root.join(line));
+                }
+            }
+
+            let packages = root.join(PACKAGES_DIR);
+            if packages.is_dir() {
+                dirs.push(packages);
+            }
+        }
+
+        Ok(dirs)
+    }
+
     pub fn on_symbol(
         &self,
         mut on_symbol: Option<Box<dyn OnSymbol + Send + Sync>>,
@@ -234,6 +294,7 @@ impl Compiler {
                 type_,
                 def,
                 decl.as_ref().map_or(false, |decl| decl.public),
+                decl.map_or_else(Doc::default, |decl| decl.doc),
             )?,
             None => {}
         })
@@ -516,6 +577,40 @@ impl Compiler {
         ))
     }
 
+    // Records that `file` is being loaded through an import (at `loc`) from `importer`, and fails
+    // if it is already being loaded further up the chain of imports. Returns the depth of the
+    // chain to restore with `finish_import` once the file is compiled.
+    //
+    pub fn start_import(&self, importer: &str, file: &str, loc: SourceLocation) -> Result<usize> {
+        let mut data = self.data.write()?;
+        let depth = data.import_stack.len();
+        if depth == 0 {
+            data.import_stack.push(Located::new(
+                importer.to_string(),
+                SourceLocation::File(importer.to_string()),
+            ));
+        }
+
+        if let Some(start) = data
+            .import_stack
+            .iter()
+            .position(|loading| is_same_file(loading.get(), file))
+        {
+            let mut chain = data.import_stack[start..].to_vec();
+            chain.push(Located::new(file.to_string(), loc));
+            data.import_stack.truncate(depth);
+            return Err(CompileError::import_cycle(chain));
+        }
+
+        data.import_stack.push(Located::new(file.to_string(), loc));
+        Ok(depth)
+    }
+
+    pub fn finish_import(&self, depth: usize) -> Result<()> {
+        self.data.write()?.import_stack.truncate(depth);
+        Ok(())
+    }
+
     pub fn set_file_contents(&self, file: String, contents: String) -> Result<()> {
         self.data.write()?.files.insert(file, contents);
         Ok(())
@@ -526,6 +621,52 @@ impl Compiler {
     }
 }
 
+fn is_same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// The project root is the closest ancestor of `folder` with a project file or packages directory
+fn find_project_root(folder: &FilePath) -> Option<PathBuf> {
+    folder
+        .ancestors()
+        .find(|dir| dir.join(PROJECT_FILE).is_file() || dir.join(PACKAGES_DIR).is_dir())
+        .map(FilePath::to_path_buf)
+}
+
+// Returns the path of the schema file for `path` within `folder`. If no such file exists, the
+// path is still returned (with the last extension we tried), so that opening it reports the error.
+//
+fn schema_file_path(folder: &FilePath, path: &ast::Path) -> PathBuf {
+    let mut file_path_buf = folder.to_path_buf();
+    for p in path {
+        file_path_buf.push(FilePath::new(p.get()));
+    }
+    for extension in SCHEMA_EXTENSIONS.iter() {
+        file_path_buf.set_extension(extension);
+        if file_path_buf.as_path().exists() {
+            break;
+        }
+    }
+    file_path_buf
+}
+
+fn find_schema_file(
+    compiler: &Compiler,
+    folder: &str,
+    path: &ast::Path,
+) -> Result<Option<PathBuf>> {
+    for dir in compiler.search_path(folder)? {
+        let file_path = schema_file_path(&dir, path);
+        if file_path.exists() {
+            return Ok(Some(file_path));
+        }
+    }
+    Ok(None)
+}
+
 pub fn lookup_schema(
     compiler: Compiler,
     schema: Ref<Schema>,
@@ -539,30 +680,32 @@ pub fn lookup_schema(
     let imported = if let Some(imported) = imported {
         imported
     } else {
+        let importer = schema.read()?.file.clone();
         let (k, v) = if let Some(root) = &schema.read()?.folder {
-            let mut file_path_buf = FilePath::new(root).to_path_buf();
-            for p in path {
-                file_path_buf.push(FilePath::new(p.get()));
-            }
-            for extension in SCHEMA_EXTENSIONS.iter() {
-                file_path_buf.set_extension(extension);
-                if file_path_buf.as_path().exists() {
-                    break;
-                }
-            }
+            let file_path_buf = match find_schema_file(&compiler, root, path)? {
+                Some(file_path) => file_path,
+                None => schema_file_path(FilePath::new(root), path),
+            };
             let file_path = file_path_buf.as_path();
 
-            let s = compile_schema_from_file(compiler.clone(), file_path)
+            let depth = compiler.start_import(
+                importer.as_str(),
+                file_path.to_str().unwrap(),
+                path_location(path),
+            )?;
+            let compiled = compile_schema_from_file(compiler.clone(), file_path)
                 .0
-                .as_result()?
-                .unwrap();
+                .as_result();
+            compiler.finish_import(depth)?;
+
+            let s = compiled?.unwrap();
             (path.clone(), s.clone())
         } else {
             return Err(CompileError::no_such_entry(path.clone()));
         };
 
         let imported = mkref(ImportedSchema {
-            args: if v.read()?.externs.len() == 0 {
+            instances: if v.read()?.externs.len() == 0 {
                 None
             } else {
                 Some(Vec::new())
@@ -598,6 +741,39 @@ pub fn lookup_path<E: Entry>(
     path: &ast::Path,
     import_global: bool,
     resolve_last: bool,
+) -> Result<(Ref<Schema>, Option<Decl<E>>, ast::Path)> {
+    lookup_path_from::<E>(
+        compiler,
+        schema,
+        path,
+        import_global,
+        resolve_last,
+        false, /* external */
+    )
+}
+
+// Looks up a path from outside of `schema` (i.e. through an import), so that every declaration
+// along it, including the first, must be public.
+//
+fn lookup_imported_path<E: Entry>(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    path: &ast::Path,
+) -> Result<(Ref<Schema>, Option<Decl<E>>, ast::Path)> {
+    lookup_path_from::<E>(
+        compiler, schema, path, false, /* import_global */
+        false, /* resolve_last */
+        true,  /* external */
+    )
+}
+
+fn lookup_path_from<E: Entry>(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    path: &ast::Path,
+    import_global: bool,
+    resolve_last: bool,
+    external: bool,
 ) -> Result<(Ref<Schema>, Option<Decl<E>>, ast::Path)> {
     if path.len() == 0 {
         return Ok((schema, None, path.clone()));
@@ -605,10 +781,14 @@ pub fn lookup_path<E: Entry>(
 
     let mut schema = schema;
     for (i, ident) in path.iter().enumerate() {
-        let check_visibility = i > 0;
-        if let Some(decl) = schema
-            .read()?
-            .get_and_check::<E>(&ident, check_visibility, path)?
+        // Anything past the first component of the path is declared in another schema, and
+        // must be exported to be visible.
+        let check_visibility = external || i > 0;
+        let visible_path = path[..i + 1].to_vec();
+        if let Some(decl) =
+            schema
+                .read()?
+                .get_and_check::<E>(&ident, check_visibility, &visible_path)?
         {
             return Ok((
                 schema.clone(),
@@ -620,7 +800,7 @@ pub fn lookup_path<E: Entry>(
         let new = if let Some(imported) =
             schema
                 .read()?
-                .get_and_check::<SchemaEntry>(&ident, check_visibility, path)?
+                .get_and_check::<SchemaEntry>(&ident, check_visibility, &visible_path)?
         {
             lookup_schema(compiler.clone(), schema.clone(), &imported.value)?
                 .read()?
@@ -629,22 +809,24 @@ pub fn lookup_path<E: Entry>(
         } else {
             match &schema.read()?.parent_scope {
                 Some(parent) => {
-                    return lookup_path::<E>(
+                    return lookup_path_from::<E>(
                         compiler.clone(),
                         parent.clone(),
                         &path[i..].to_vec(),
                         import_global,
                         resolve_last,
+                        external,
                     )
                 }
                 None => {
                     if import_global {
-                        return lookup_path::<E>(
+                        return lookup_path_from::<E>(
                             compiler.clone(),
                             compiler.builtins(),
                             &path[i..].to_vec(),
                             false, /* import_global */
                             resolve_last,
+                            external,
                         );
                     } else {
                         return Ok((schema.clone(), None, path[i..].to_vec()));
@@ -697,35 +879,117 @@ pub fn resolve_type(
             Ok(decl.value.clone())
         }
         ast::TypeBody::Struct(entries) => {
-            let mut fields = Vec::new();
-            let mut seen = BTreeSet::new();
+            // Each entry resolves to a record type, which is either a single field or the fields of
+            // an included type (which may not be known yet). We keep track of the location that
+            // introduced each group of fields, so that duplicates point at the right place.
+            let mut groups = Vec::new();
+            let mut group_locs = Vec::new();
             for e in entries {
                 match e {
                     ast::StructEntry::NameAndType(nt) => {
-                        if seen.contains(&nt.name) {
-                            return Err(CompileError::duplicate_entry(vec![nt.name.clone()]));
-                        }
-                        seen.insert(nt.name.clone());
-                        fields.push(MField {
-                            name: nt.name.get().clone(),
-                            type_: resolve_type(compiler.clone(), schema.clone(), &nt.def)?,
-                            nullable: true, /* TODO: implement non-null types */
-                        });
+                        groups.push(mkcref(MType::Record(Located::new(
+                            vec![MField {
+                                name: nt.name.get().clone(),
+                                type_: resolve_type(compiler.clone(), schema.clone(), &nt.def)?,
+                                nullable: nt.nullable,
+                                loc: nt.name.location().clone(),
+                            }],
+                            nt.name.location().clone(),
+                        ))));
+                        group_locs.push(nt.name.location().clone());
                     }
-                    ast::StructEntry::Include { .. } => {
-                        return Err(CompileError::unimplemented(loc, "Struct inclusions"));
+                    ast::StructEntry::Rest => {
+                        return Err(CompileError::unimplemented(
+                            loc.clone(),
+                            "open record types (`..`) outside of generic bounds",
+                        ))
+                    }
+                    ast::StructEntry::Include(path) => {
+                        let included = resolve_type(
+                            compiler.clone(),
+                            schema.clone(),
+                            &ast::Type {
+                                body: ast::TypeBody::Reference(path.clone()),
+                                start: ast.start.clone(),
+                                end: ast.end.clone(),
+                            },
+                        )?;
+
+                        group_locs.push(path_location(path));
+
+                        let path = path.clone();
+                        groups.push(included.then(move |included: Ref<MType>| {
+                            match &*included.read()? {
+                                MType::Record(fields) => Ok(mkcref(MType::Record(Located::new(
+                                    fields.get().clone(),
+                                    path_location(&path),
+                                )))),
+                                other => Err(CompileError::wrong_type(
+                                    &MType::Record(Located::new(Vec::new(), path_location(&path))),
+                                    other,
+                                )),
+                            }
+                        })?);
                     }
                 }
             }
 
-            Ok(mkcref(MType::Record(Located::new(fields, loc))))
+            combine_crefs(groups)?.then(move |groups: Ref<Vec<Ref<MType>>>| {
+                let mut fields = Vec::new();
+                let mut seen = BTreeSet::new();
+                for (group, group_loc) in groups.read()?.iter().zip(group_locs.iter()) {
+                    let group = group.read()?;
+                    let group_fields = match &*group {
+                        MType::Record(fields) => fields.get(),
+                        _ => {
+                            return Err(CompileError::internal(
+                                group_loc.clone(),
+                                "Struct entries should resolve to records",
+                            ))
+                        }
+                    };
+
+                    for field in group_fields {
+                        if seen.contains(&field.name) {
+                            return Err(CompileError::duplicate_entry(vec![Ident::with_location(
+                                group_loc.clone(),
+                                field.name.clone(),
+                            )]));
+                        }
+                        seen.insert(field.name.clone());
+                        fields.push(field.clone());
+                    }
+                }
+
+                Ok(mkcref(MType::Record(Located::new(fields, loc.clone()))))
+            })
         }
         ast::TypeBody::List(inner) => Ok(mkcref(MType::List(Located::new(
             resolve_type(compiler, schema, inner.as_ref())?,
             loc,
         )))),
-        ast::TypeBody::Exclude { .. } => {
-            return Err(CompileError::unimplemented(loc, "Struct exclusions"));
+        ast::TypeBody::Exclude { inner, excluded } => {
+            let inner = resolve_type(compiler, schema, inner.as_ref())?;
+            let excluded = excluded.clone();
+            inner.then(move |inner: Ref<MType>| exclude_fields(&loc, &*inner.read()?, &excluded))
+        }
+        ast::TypeBody::Parameterized(path, args) => {
+            // Only builtin types take arguments, so there is nothing to look up.
+            let name = path_location(path);
+            let name = match path.as_slice() {
+                [name] => name.get(),
+                _ => {
+                    return Err(CompileError::type_args(
+                        name,
+                        &path.pretty(),
+                        "type does not take arguments",
+                    ))
+                }
+            };
+            Ok(mkcref(MType::Atom(Located::new(
+                parameterized_type(compiler.engine()?, &loc, &name.to_string(), args)?,
+                loc,
+            ))))
         }
         ast::TypeBody::Generic(path, types) => {
             let args = types
@@ -733,20 +997,147 @@ pub fn resolve_type(
                 .map(|t| resolve_type(compiler.clone(), schema.clone(), t))
                 .collect::<Result<Vec<_>>>()?;
 
-            // Since generic names are hardcoded right now, expect the name to be a single element.
-            // Eventually, this should be a decl lookup though.
-            let name = if path.len() == 1 {
-                path[0].get()
-            } else {
-                return Err(CompileError::unimplemented(loc, "Multi-part generic names"));
-            };
+            // Builtin generics are not declared in any schema, so check for them first.
+            if path.len() == 1 {
+                if let Some(builtin) = GLOBAL_GENERICS.get(path[0].get()) {
+                    let generic = builtin.new(&loc, args)?;
+                    return Ok(mkcref(MType::Generic(Located::new(generic, loc))));
+                }
+            }
+
+            // Otherwise, the generic is declared (or imported) like any other type.
+            let (_, decl, r) = lookup_path::<CRef<MType>>(
+                compiler.clone(),
+                schema.clone(),
+                &path,
+                true, /* import_global */
+                true, /* resolve_last */
+            )?;
+            if r.len() > 0 {
+                return Err(CompileError::no_such_entry(r));
+            }
+            let decl = decl.ok_or_else(|| CompileError::no_such_entry(path.clone()))?;
+            if let Some(ident) = path.last() {
+                compiler.run_on_symbol(
+                    ident.clone(),
+                    SymbolKind::Type,
+                    SType::new_mono(decl.value.clone()),
+                    decl.name.location().clone(),
+                    Some(decl.clone()),
+                )?;
+            }
+
+            let path = path.clone();
+            decl.value.then(move |generic: Ref<MType>| {
+                match &*generic.read()? {
+                    MType::Generic(generic) => {
+                        if let Some(generic) = as_generic::<UserGenericType>(generic.get().as_ref())
+                        {
+                            return generic.instantiate(&loc, args.clone());
+                        }
+                    }
+                    _ => {}
+                }
+                Err(CompileError::wrong_kind(
+                    path.clone(),
+                    "generic type",
+                    "non-generic type",
+                ))
+            })
+        }
+        ast::TypeBody::Union(variants) => {
+            let mut seen = BTreeSet::new();
+            let mut fields = Vec::new();
+            for variant in variants {
+                if seen.contains(variant.name.get()) {
+                    return Err(CompileError::duplicate_entry(vec![variant.name.clone()]));
+                }
+                seen.insert(variant.name.get().clone());
 
-            let generic = match GLOBAL_GENERICS.get(name).map(|g| g.new(&loc, args)) {
-                Some(generic) => generic?,
-                None => return Err(CompileError::no_such_entry(path.clone())),
+                fields.push(MField {
+                    loc: variant.name.location().clone(),
+                    ..MField::new_nullable(
+                        variant.name.get().clone(),
+                        resolve_type(compiler.clone(), schema.clone(), &variant.def)?,
+                    )
+                });
+            }
+
+            Ok(mkcref(MType::Union(Located::new(fields, loc))))
+        }
+        ast::TypeBody::Enum(members) => {
+            let mut seen = BTreeSet::new();
+            for member in members {
+                if !seen.insert(member) {
+                    return Err(CompileError::duplicate_entry(vec![Ident::with_location(
+                        loc.clone(),
+                        member.clone(),
+                    )]));
+                }
+            }
+
+            Ok(mkcref(MType::Atom(Located::new(
+                AtomicType::Enum(members.clone()),
+                loc,
+            ))))
+        }
+        ast::TypeBody::Fn { args, ret } => {
+            // Function types don't name their arguments, so they are named by position. Functions
+            // are matched against them positionally (see MType::unify).
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    Ok(MField::new_nullable(
+                        format!("arg{}", i).into(),
+                        resolve_type(compiler.clone(), schema.clone(), arg)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let ret = resolve_type(compiler, schema, ret.as_ref())?;
+            Ok(mkcref(MType::Fn(Located::new(
+                MFnType {
+                    args,
+                    ret,
+                    anonymous: true,
+                },
+                loc,
+            ))))
+        }
+        ast::TypeBody::Dictionary { key, value } => {
+            let key = match key {
+                Some(key) => resolve_type(compiler.clone(), schema.clone(), key.as_ref())?,
+                None => mkcref(MType::Atom(Located::new(AtomicType::Int32, loc.clone()))),
             };
+            let value = resolve_type(compiler, schema, value.as_ref())?;
+
+            combine_crefs(vec![key, value])?.then(move |kv: Ref<Vec<Ref<MType>>>| {
+                let kv = kv.read()?;
+                let (key, value) = (kv[0].read()?, kv[1].read()?);
+                let key = match &*key {
+                    MType::Atom(key) if is_integer(key.get()) => key.get().clone(),
+                    _ => {
+                        return Err(CompileError::wrong_type(
+                            &MType::Atom(Located::new(AtomicType::Int32, key.location())),
+                            &*key,
+                        ))
+                    }
+                };
+                let value = match &*value {
+                    MType::Atom(value) => value.get().clone(),
+                    _ => {
+                        return Err(CompileError::unimplemented(
+                            value.location(),
+                            format!("dictionary of {:?}", &*value).as_str(),
+                        ))
+                    }
+                };
 
-            Ok(mkcref(MType::Generic(Located::new(generic, loc))))
+                Ok(mkcref(MType::Atom(Located::new(
+                    AtomicType::Dictionary(Box::new(key), Box::new(value)),
+                    loc.clone(),
+                ))))
+            })
         }
     }
 }
@@ -776,17 +1167,147 @@ pub fn find_field<'a>(fields: &'a Vec<MField>, name: &Ident) -> Option<&'a MFiel
 }
 
 impl SType {
-    pub fn instantiate(&self) -> Result<CRef<MType>> {
+    // Replaces the type's variables with fresh unknowns. `loc` is where the type is used (e.g. the
+    // name of a function at its call site), which is where violations of the variables' bounds are
+    // reported.
+    //
+    pub fn instantiate(&self, loc: &SourceLocation) -> Result<CRef<MType>> {
         let variables: BTreeMap<_, _> = self
             .variables
             .iter()
             .map(|n| (n.clone(), MType::new_unknown(n.as_str())))
             .collect();
 
+        for (name, bound) in &self.bounds {
+            if let Some(variable) = variables.get(name) {
+                let (loc, name, bound) = (loc.clone(), name.clone(), bound.clone());
+                variable.then(move |type_: Ref<MType>| {
+                    let (loc, name, open) = (loc.clone(), name.clone(), bound.open);
+                    bound.type_.then(move |bound: Ref<MType>| {
+                        check_bound(&loc, &name, &*bound.read()?, open, &*type_.read()?)?;
+                        Ok(mkcref(()))
+                    })
+                })?;
+            }
+        }
+
         return Ok(self.body.substitute(&variables)?);
     }
 }
 
+// Resolves the bound of a generic argument, which is a record type that may end with `..` to allow
+// any other fields.
+//
+fn resolve_bound(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    bound: &ast::Type,
+) -> Result<GenericBound> {
+    Ok(match &bound.body {
+        ast::TypeBody::Struct(entries) => GenericBound {
+            type_: resolve_type(
+                compiler,
+                schema,
+                &ast::Type {
+                    body: ast::TypeBody::Struct(
+                        entries
+                            .iter()
+                            .filter(|e| !matches!(e, ast::StructEntry::Rest))
+                            .cloned()
+                            .collect(),
+                    ),
+                    start: bound.start.clone(),
+                    end: bound.end.clone(),
+                },
+            )?,
+            open: entries.iter().any(|e| matches!(e, ast::StructEntry::Rest)),
+        },
+        _ => GenericBound {
+            type_: resolve_type(compiler, schema, bound)?,
+            open: false,
+        },
+    })
+}
+
+// Checks that the type a generic variable is instantiated with has the fields of its bound (and,
+// unless the bound is open, no others).
+//
+fn check_bound(
+    loc: &SourceLocation,
+    name: &Ident,
+    bound: &MType,
+    open: bool,
+    type_: &MType,
+) -> Result<()> {
+    let bound = match bound {
+        MType::Record(fields) => fields,
+        _ => {
+            return Err(CompileError::generic_bound(
+                loc.clone(),
+                name,
+                &format!("the bound must be a record type, not {}", bound.pretty()),
+            ))
+        }
+    };
+    let fields = match type_ {
+        MType::Record(fields) => fields,
+        _ => {
+            return Err(CompileError::generic_bound(
+                loc.clone(),
+                name,
+                &format!("expected a record, not {}", type_.pretty()),
+            ))
+        }
+    };
+
+    for required in bound.iter() {
+        match find_field(fields.get(), &required.name) {
+            Some(field) => {
+                if required.type_.unify(&field.type_).is_err() {
+                    let required_type = required
+                        .type_
+                        .must()
+                        .context(RuntimeSnafu { loc: loc.clone() })?;
+                    return Err(CompileError::generic_bound(
+                        loc.clone(),
+                        name,
+                        &format!(
+                            "field {} must have type {}",
+                            required.name,
+                            required_type.read()?.pretty()
+                        ),
+                    ));
+                }
+            }
+            None => {
+                return Err(CompileError::generic_bound(
+                    loc.clone(),
+                    name,
+                    &format!("missing field {}", required.name),
+                ))
+            }
+        }
+    }
+
+    if !open {
+        if let Some(extra) = fields
+            .iter()
+            .find(|f| find_field(bound.get(), &f.name).is_none())
+        {
+            return Err(CompileError::generic_bound(
+                loc.clone(),
+                name,
+                &format!(
+                    "unexpected field {} (end the bound with `..` to allow it)",
+                    extra.name
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn typecheck_path(type_: CRef<MType>, path: &[Located<Ident>]) -> Result<CRef<MType>> {
     if path.len() == 0 {
         return Ok(type_);
@@ -828,6 +1349,17 @@ pub fn typecheck_path(type_: CRef<MType>, path: &[Located<Ident>]) -> Result<CRe
 }
 
 fn compile_expr(compiler: Compiler, schema: Ref<Schema>, expr: &ast::Expr) -> Result<CTypedExpr> {
+    compile_scoped_expr(compiler, schema, SQLScope::new(None), expr)
+}
+
+// Compiles an expression, resolving any SQL names in it against `scope`.
+//
+fn compile_scoped_expr(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    scope: Ref<SQLScope>,
+    expr: &ast::Expr,
+) -> Result<CTypedExpr> {
     let loc = SourceLocation::Range(
         schema.read()?.file.clone(),
         Range {
@@ -836,40 +1368,196 @@ fn compile_expr(compiler: Compiler, schema: Ref<Schema>, expr: &ast::Expr) -> Re
         },
     );
 
+    // A model query is compiled as the SQL query that it expands to.
+    //
+    if let ast::ExprBody::ModelQuery(query) = &expr.body {
+        return compile_model_query(compiler, schema, &loc, query, expr.is_unsafe);
+    }
+
     if expr.is_unsafe {
         compile_unsafe_expr(compiler, schema, &expr.body, &loc)
     } else {
         match &expr.body {
-            ast::ExprBody::SQLQuery(q) => {
-                let (_scope, type_, query) =
-                    compile_sqlquery(compiler.clone(), schema.clone(), None, &loc, q)?;
-                Ok(CTypedExpr {
-                    type_,
-                    expr: compiler.async_cref(async move {
-                        let query = cunwrap(query.await?)?;
-                        Ok(mkcref(Expr::SQL(Arc::new(SQL {
-                            names: query.names,
-                            body: SQLBody::Query(query.body),
-                        }))))
-                    })?,
-                })
+            ast::ExprBody::SQLQuery(q) => compile_query_expr(compiler, schema, &loc, q),
+            ast::ExprBody::SQLExpr(e) => Ok(compile_sqlexpr(
+                compiler.clone(),
+                schema.clone(),
+                scope,
+                &loc,
+                e,
+            )?),
+            ast::ExprBody::ModelQuery(_) => Err(CompileError::internal(
+                loc.clone(),
+                "model queries must be expanded before they are compiled",
+            )),
+        }
+    }
+}
+
+pub fn compile_query_expr(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    loc: &SourceLocation,
+    q: &sqlast::Query,
+) -> Result<CTypedExpr> {
+    let (_scope, type_, query) = compile_sqlquery(compiler.clone(), schema.clone(), None, loc, q)?;
+    Ok(CTypedExpr {
+        type_,
+        expr: compiler.async_cref(async move {
+            let query = cunwrap(query.await?)?;
+            Ok(mkcref(Expr::SQL(Arc::new(SQL {
+                names: query.names,
+                body: SQLBody::Query(query.body),
+            }))))
+        })?,
+    })
+}
+
+pub fn rebind_decl<E: Entry>(schema: SchemaInstance, decl: &Decl<E>) -> Result<E> {
+    if schema.id.is_none() {
+        return Ok(decl.value.clone());
+    }
+
+    // An instance is compiled from the same source as the schema that `decl` belongs to, so
+    // its counterpart has the same name.
+    let instance = schema.schema.read()?;
+    let rebound = instance
+        .get_decls::<E>()
+        .get(decl.name.get())
+        .ok_or_else(|| {
+            CompileError::internal(
+                decl.name.location().clone(),
+                format!(
+                    "Could not find declaration {} in schema instance",
+                    decl.name
+                )
+                .as_str(),
+            )
+        })?;
+    Ok(rebound.value.clone())
+}
+
+fn check_import_args(
+    imported: Ref<ImportedSchema>,
+    path: &ast::Path,
+    args: &Option<Vec<ast::NameAndExpr>>,
+) -> Result<()> {
+    let args = match (imported.read()?.instances.is_some(), args) {
+        (false, None) => return Ok(()),
+        (true, Some(args)) => args,
+        (true, None) => {
+            return Err(CompileError::import_error(
+                path.clone(),
+                "Arguments are not provided to module with extern declarations",
+            ))
+        }
+        (false, Some(_)) => {
+            return Err(CompileError::import_error(
+                path.clone(),
+                "Arguments should not be provided to module without extern declarations",
+            ))
+        }
+    };
+
+    let externs = imported.read()?.schema.read()?.externs.clone();
+    let mut seen = BTreeSet::new();
+    for arg in args {
+        if !seen.insert(arg.name.get().clone()) {
+            return Err(CompileError::duplicate_entry(vec![arg.name.clone()]));
+        }
+        if !externs.contains_key(arg.name.get()) {
+            return Err(CompileError::no_such_entry(vec![arg.name.clone()]));
+        }
+    }
+
+    for name in externs.keys() {
+        if !seen.contains(name) {
+            return Err(CompileError::missing_arg(vec![Ident::with_location(
+                path_location(path),
+                name.clone(),
+            )]));
+        }
+    }
+
+    Ok(())
+}
+
+// Compiles a fresh copy of an imported schema whose externs are bound to the importer's
+// arguments. Each extern is typechecked against its argument as the instance compiles.
+//
+fn instantiate_schema(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    imported: Ref<ImportedSchema>,
+    args: &Vec<ast::NameAndExpr>,
+) -> Result<SchemaInstance> {
+    let mut extern_args = BTreeMap::new();
+    for arg in args {
+        let compiled = match &arg.expr {
+            Some(expr) => compile_expr(compiler.clone(), schema.clone(), expr)?,
+            None => {
+                // `{ name }` is shorthand for `{ name: name }`
+                let te =
+                    compile_reference(compiler.clone(), schema.clone(), &vec![arg.name.clone()])?;
+                CTypedExpr {
+                    type_: te.type_.clone(),
+                    expr: mkcref(te.expr.as_ref().clone()),
+                }
+            }
+        };
+
+        extern_args.insert(
+            arg.name.get().clone(),
+            CTypedNameAndExpr {
+                name: arg.name.get().clone(),
+                type_: compiled.type_,
+                expr: compiled.expr,
+            },
+        );
+    }
+
+    let file = imported.read()?.schema.read()?.file.clone();
+    let (file, folder, ast) = compiler.open_file(FilePath::new(&file))?;
+    let instance = Schema::new(file, folder);
+    instance.write()?.extern_args = extern_args;
+    compile_schema_ast(compiler.clone(), instance.clone(), &ast).as_result()?;
+
+    let mut imported = imported.write()?;
+    let instances = imported.instances.get_or_insert_with(Vec::new);
+    instances.push(instance.clone());
+    Ok(SchemaInstance::instance(instance, instances.len() - 1))
+}
+
+fn bind_instance_decls<E: Entry>(
+    compiler: Compiler,
+    decls: &DeclMap<E>,
+    instance: SchemaInstance,
+    list: &ast::ImportList,
+    mut bind: impl FnMut(&Located<Ident>, E) -> Result<()>,
+) -> Result<()> {
+    match list {
+        ast::ImportList::None => {}
+        ast::ImportList::Star => {
+            for (_, decl) in decls.iter().filter(|(_, v)| v.public) {
+                bind(&decl.name, rebind_decl(instance.clone(), decl)?)?;
             }
-            ast::ExprBody::SQLExpr(e) => {
-                let scope = SQLScope::new(None);
-                Ok(compile_sqlexpr(
+        }
+        ast::ImportList::Items(items) => {
+            // Items may be nested within other schemas, so resolve them against the instance
+            // directly.
+            for item in items {
+                let (_, decl, r) = lookup_imported_path::<E>(
                     compiler.clone(),
-                    schema.clone(),
-                    scope,
-                    &loc,
-                    e,
-                )?)
+                    instance.schema.clone(),
+                    &item.path,
+                )?;
+                if let (Some(decl), true) = (decl, r.is_empty()) {
+                    bind(item.name(), decl.value)?;
+                }
             }
         }
     }
-}
-
-pub fn rebind_decl<E: Entry>(_schema: SchemaInstance, decl: &Decl<E>) -> Result<E> {
-    Ok(decl.value.clone())
+    Ok(())
 }
 
 fn compile_schema_from_file(
@@ -947,7 +1635,9 @@ fn declare_schema_entries(
     result
 }
 
-type Declaration<T> = (Located<Ident>, bool, T);
+// A declaration's name, whether it's an extern, its value, and its documentation.
+//
+type Declaration<T> = (Located<Ident>, bool, T, Doc);
 
 fn import_all_decls<E: Entry>(
     decls: &DeclMap<E>,
@@ -959,6 +1649,7 @@ fn import_all_decls<E: Entry>(
             v.name.clone(),
             false, /* extern_ */
             rebind_decl(imported_schema.clone(), &v)?,
+            v.doc.clone(),
         ));
     }
     Ok(ret)
@@ -968,43 +1659,180 @@ fn import_named_decl<E: Entry>(
     compiler: Compiler,
     imported: Ref<ImportedSchema>,
     imported_schema: SchemaInstance,
-    item: &ast::Path,
+    item: &ast::ImportItem,
 ) -> Result<Declaration<E>> {
-    let loc = path_location(item);
-    if item.len() != 1 {
-        return Err(CompileError::unimplemented(loc, "path imports"));
-    }
-
-    let (_, decl, r) = lookup_path::<E>(
+    let (_, decl, r) = lookup_imported_path::<E>(
         compiler.clone(),
         imported.read()?.schema.clone(),
-        &item,
-        false, /* import_global */
-        false, /* resolve_last */
+        &item.path,
     )?;
     if r.len() > 0 {
         return Err(CompileError::no_such_entry(r.clone()));
     }
-    let decl = decl.ok_or_else(|| CompileError::no_such_entry(r))?;
+    let decl = decl.ok_or_else(|| CompileError::no_such_entry(item.path.clone()))?;
 
-    if let Some(ident) = item.last() {
+    if let Some(ident) = item.path.last() {
         run_on_decl(compiler.clone(), ident.clone(), &decl)?;
     }
 
     Ok((
-        item[0].clone(),
+        item.name().clone(),
         false, /* extern_ */
         rebind_decl(imported_schema, &decl)?,
+        decl.doc.clone(),
     ))
 }
 
+// Imports the exported overloads of a function imported by name, if there are any, under the
+// item's (possibly aliased) name.
+//
+fn import_overloads(
+    compiler: Compiler,
+    imported: Ref<ImportedSchema>,
+    item: &ast::ImportItem,
+) -> Result<Vec<Declaration<ExprEntry>>> {
+    let (declared, decl, r) = lookup_imported_path::<ExprEntry>(
+        compiler.clone(),
+        imported.read()?.schema.clone(),
+        &item.path,
+    )?;
+    let decl = match decl {
+        Some(decl) if r.len() == 0 => decl,
+        _ => return Ok(Vec::new()),
+    };
+
+    let declared = declared.read()?;
+    Ok(exported_overloads(&declared, decl.name.get())
+        .map(|overload| {
+            (
+                item.name().clone(),
+                false, /* extern_ */
+                overload.value.clone(),
+                overload.doc.clone(),
+            )
+        })
+        .collect())
+}
+
+fn import_all_overloads(imported: &Schema) -> Vec<Declaration<ExprEntry>> {
+    imported
+        .overloads
+        .keys()
+        .flat_map(|name| exported_overloads(imported, name))
+        .map(|overload| {
+            (
+                overload.name.clone(),
+                false, /* extern_ */
+                overload.value.clone(),
+                overload.doc.clone(),
+            )
+        })
+        .collect()
+}
+
+fn exported_overloads<'a>(
+    schema: &'a Schema,
+    name: &Ident,
+) -> impl Iterator<Item = &'a Located<Decl<ExprEntry>>> {
+    schema
+        .overloads
+        .get(name)
+        .into_iter()
+        .flatten()
+        .filter(|overload| overload.public)
+}
+
+// Binds the overloads of the functions imported from a schema instance, in the same order as
+// they were declared by import_overloads or import_all_overloads (see bind_instance_decls).
+//
+fn bind_instance_overloads(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    instance: SchemaInstance,
+    list: &ast::ImportList,
+) -> Result<()> {
+    let bind = |name: &Ident, instance_schema: &Schema, instance_name: &Ident| -> Result<()> {
+        let s = schema.read()?;
+        for (decl, overload) in s
+            .overloads
+            .get(name)
+            .into_iter()
+            .flatten()
+            .zip(exported_overloads(instance_schema, instance_name))
+        {
+            decl.value.unify(&overload.value)?;
+        }
+        Ok(())
+    };
+
+    match list {
+        ast::ImportList::None => {}
+        ast::ImportList::Star => {
+            let instance_schema = instance.schema.read()?;
+            for name in instance_schema.overloads.keys() {
+                bind(name, &*instance_schema, name)?;
+            }
+        }
+        ast::ImportList::Items(items) => {
+            for item in items {
+                let (declared, decl, r) = lookup_imported_path::<ExprEntry>(
+                    compiler.clone(),
+                    instance.schema.clone(),
+                    &item.path,
+                )?;
+                if let (Some(decl), true) = (decl, r.is_empty()) {
+                    bind(item.name().get(), &*declared.read()?, decl.name.get())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// A simple import (`import a.b.c`) names a schema if `a/b/c` is a schema file. Otherwise, it
+// names an entry nested within the longest prefix that is one (e.g. `c` within `a/b`), and is
+// treated like `import c from a.b`.
+//
+fn resolve_simple_import(
+    compiler: &Compiler,
+    schema: &Ref<Schema>,
+    path: &ast::Path,
+    alias: &Option<Located<Ident>>,
+) -> Result<(ast::Path, ast::ImportList)> {
+    let s = schema.read()?;
+    let is_schema = |prefix: &[Located<Ident>]| -> Result<bool> {
+        let prefix = prefix.to_vec();
+        Ok(s.imports.contains_key(&prefix.to_idents())
+            || match &s.folder {
+                Some(folder) => find_schema_file(compiler, folder, &prefix)?.is_some(),
+                None => false,
+            })
+    };
+
+    if !is_schema(path)? {
+        for i in (1..path.len()).rev() {
+            if is_schema(&path[..i])? {
+                return Ok((
+                    path[..i].to_vec(),
+                    ast::ImportList::Items(vec![ast::ImportItem {
+                        path: path[i..].to_vec(),
+                        alias: alias.clone(),
+                    }]),
+                ));
+            }
+        }
+    }
+
+    Ok((path.clone(), ast::ImportList::None))
+}
+
 fn add_decls<E: Entry>(
     decls: &mut DeclMap<E>,
     entries: Vec<Declaration<E>>,
     loc: &SourceLocation,
     stmt: &ast::Stmt,
 ) -> Result<()> {
-    for (name, extern_, value) in &entries {
+    for (name, extern_, value, doc) in &entries {
         if decls.contains_key(name) {
             return Err(CompileError::duplicate_entry(vec![name.clone()]));
         }
@@ -1018,6 +1846,7 @@ fn add_decls<E: Entry>(
                     fn_arg: false,
                     name: name.clone(),
                     value: value.clone(),
+                    doc: doc.clone(),
                 },
                 loc.clone(),
             ),
@@ -1036,101 +1865,45 @@ fn declare_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
     );
 
     let (mut schema_decls, mut type_decls, mut expr_decls) = (Vec::new(), Vec::new(), Vec::new());
+    let mut overloads = Vec::new();
+    let mut models = Vec::new();
+    let doc = Doc {
+        text: stmt.doc.clone(),
+        fields: BTreeMap::new(),
+    };
 
     match &stmt.body {
         ast::StmtBody::Noop | ast::StmtBody::Unparsed => {}
-        ast::StmtBody::Expr(_) => {}
-        ast::StmtBody::Import { path, list, .. } => {
+        ast::StmtBody::Expr(_) | ast::StmtBody::Test { .. } | ast::StmtBody::Assert(_) => {}
+        ast::StmtBody::Model(model) => {
+            models.push((model.name.clone(), model.clone(), None));
+        }
+        ast::StmtBody::Import {
+            path,
+            list,
+            args,
+            alias,
+        } => {
+            let (path, list) = match list {
+                ast::ImportList::None => resolve_simple_import(compiler, schema, path, alias)?,
+                list => (path.clone(), list.clone()),
+            };
             let imported = lookup_schema(compiler.clone(), schema.clone(), &path)?;
-            if imported.read()?.args.is_some() {
-                return Err(CompileError::unimplemented(
-                    loc.clone(),
-                    "Importing with arguments",
-                ));
-            }
+            check_import_args(imported.clone(), &path, args)?;
 
-            // XXX Importing schemas with extern values is currently broken, because we don't
-            // actually "inject" any meaningful reference to imported_schema's id into the decl
-            // during rebind_decl.  We should figure out how to generate a new set of decls for
-            // the imported schema (w/ the imported args)
-            //
-            // let checked = match args {
-            //     None => None,
-            //     Some(args) => {
-            //         let mut externs = imported.read()?.schema.read()?.externs.clone();
-            //         let mut checked = BTreeMap::new();
-            //         for arg in args {
-            //             let expr = match &arg.expr {
-            //                 None => ast::Expr::SQLExpr(sqlast::Expr::CompoundIdentifier(vec![
-            //                     sqlast::Ident {
-            //                         value: arg.name.clone(),
-            //                         quote_style: None,
-            //                     },
-            //                 ])),
-            //                 Some(expr) => expr.clone(),
-            //             };
-
-            //             if checked.get(&arg.name).is_some() {
-            //                 return Err(CompileError::duplicate_entry(vec![arg.name.clone()]));
-            //             }
-
-            //             if let Some(extern_) = externs.get_mut(&arg.name) {
-            //                 let compiled = compile_expr(schema.clone(), &expr)?;
-
-            //                 extern_.unify(&compiled.type_)?;
-            //                 checked.insert(
-            //                     arg.name.clone(),
-            //                     TypedNameAndExpr {
-            //                         name: arg.name.clone(),
-            //                         type_: extern_.clone(),
-            //                         expr: compiled.expr,
-            //                     },
-            //                 );
-            //             } else {
-            //                 return Err(CompileError::no_such_entry(vec![arg.name.clone()]));
-            //             }
-            //         }
-
-            //         Some(checked)
-            //     }
-            // };
-
-            // let id = {
-            //     let imported_args = &mut imported.write()?.args;
-            //     if let Some(imported_args) = imported_args {
-            //         if let Some(checked) = checked {
-            //             let id = imported_args.len();
-            //             imported_args.push(checked);
-            //             Some(id)
-            //         } else {
-            //             return Err(CompileError::import_error(
-            //                 path.clone(),
-            //                 "Arguments are not provided to module with extern declarations",
-            //             ));
-            //         }
-            //     } else if args.is_some() {
-            //         return Err(CompileError::import_error(
-            //               path.clone(),
-            //             "Arguments should not be provided to module without extern declarations",
-            //         ));
-            //     } else {
-            //         None
-            //     }
-            // };
-
-            match list {
+            match &list {
                 ast::ImportList::None => {
                     schema_decls.push((
-                        path.last().unwrap().clone(),
+                        alias
+                            .clone()
+                            .unwrap_or_else(|| path.last().unwrap().clone()),
                         false, /* extern_ */
                         path.clone(),
+                        doc.clone(),
                     ));
                 }
                 ast::ImportList::Star => {
-                    let imported_schema = SchemaInstance {
-                        schema: imported.read()?.schema.clone(),
-                        id: None,
-                    };
+                    let imported_schema = SchemaInstance::global(imported.read()?.schema.clone());
                     schema_decls.extend(import_all_decls(
                         &imported.read()?.schema.read()?.schema_decls,
                         imported_schema.clone(),
@@ -1143,17 +1916,16 @@ fn declare_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                         &imported.read()?.schema.read()?.expr_decls,
                         imported_schema.clone(),
                     )?);
+                    overloads.extend(import_all_overloads(&imported.read()?.schema.read()?));
+                    models.extend(import_all_models(&imported.read()?.schema)?);
                 }
                 ast::ImportList::Items(items) => {
-                    let imported_schema = SchemaInstance {
-                        schema: schema.clone(),
-                        id: None,
-                    };
-
-                    let mut found = false;
-                    let mut err = None;
+                    let imported_schema = SchemaInstance::global(imported.read()?.schema.clone());
 
                     for item in items {
+                        let mut found = false;
+                        let mut err = None;
+
                         match import_named_decl::<SchemaEntry>(
                             compiler.clone(),
                             imported.clone(),
@@ -1189,46 +1961,195 @@ fn declare_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                             Ok(decl) => {
                                 found = true;
                                 expr_decls.push(decl);
+                                overloads.extend(import_overloads(
+                                    compiler.clone(),
+                                    imported.clone(),
+                                    item,
+                                )?);
                             }
                             Err(e) => err = Some(e),
                         };
 
+                        if let Some(model) = import_model(&imported.read()?.schema, item)? {
+                            found = true;
+                            models.push(model);
+                        }
+
                         if !found {
                             return Err(err.unwrap());
                         }
                     }
                 }
             }
+
+            if args.is_some() && !models.is_empty() {
+                return Err(CompileError::unimplemented(
+                    loc.clone(),
+                    "importing models from parameterized imports",
+                ));
+            }
+
+            if args.is_some() {
+                // The imported values are bound to the schema instance in compile_schema_entry,
+                // once the arguments can be compiled.
+                for (name, _, value, _) in type_decls.iter_mut() {
+                    *value = MType::new_unknown(name.get().as_ref());
+                }
+                for (name, _, value, _) in expr_decls.iter_mut().chain(overloads.iter_mut()) {
+                    *value = STypedExpr::new_unknown(name.get().as_ref());
+                }
+            }
         }
-        ast::StmtBody::TypeDef(nt) => type_decls.push((
-            nt.name.clone(),
-            false, /* extern_ */
-            MType::new_unknown(nt.name.get().as_ref()),
-        )),
-        ast::StmtBody::FnDef { name, .. } => expr_decls.push((
+        ast::StmtBody::TypeDef { name, def, .. } => type_decls.push((
             name.clone(),
             false, /* extern_ */
-            STypedExpr::new_unknown(name.get().as_ref()),
+            MType::new_unknown(name.get().as_ref()),
+            Doc {
+                fields: field_docs(def),
+                ..doc.clone()
+            },
         )),
+        ast::StmtBody::FnDef { name, .. } => {
+            let decl = (
+                name.clone(),
+                false, /* extern_ */
+                STypedExpr::new_unknown(name.get().as_ref()),
+                doc.clone(),
+            );
+
+            // A function that is declared again with the same name is an overload of it.
+            //
+            let mut s = schema.write()?;
+            if s.overloads.contains_key(name.get()) {
+                overloads.push(decl);
+            } else {
+                s.overloads.insert(name.get().clone(), Vec::new());
+                expr_decls.push(decl);
+            }
+        }
         ast::StmtBody::Let { name, .. } => expr_decls.push((
             name.clone(),
             false, /* extern_ */
             STypedExpr::new_unknown(name.get().as_ref()),
+            doc.clone(),
         )),
         ast::StmtBody::Extern { name, .. } => expr_decls.push((
             name.clone(),
             true, /* extern_ */
             STypedExpr::new_unknown(name.get().as_ref()),
+            doc.clone(),
         )),
     };
 
     add_decls(&mut schema.write()?.schema_decls, schema_decls, &loc, stmt)?;
     add_decls(&mut schema.write()?.type_decls, type_decls, &loc, stmt)?;
     add_decls(&mut schema.write()?.expr_decls, expr_decls, &loc, stmt)?;
+    add_overloads(&mut schema.write()?.overloads, overloads, &loc, stmt);
+    add_models(&mut schema.write()?.models, models, stmt)?;
+
+    Ok(())
+}
 
+type ModelDeclaration = (Located<Ident>, ast::Model, Option<SchemaRef>);
+
+fn add_models(
+    models: &mut BTreeMap<Ident, ModelDecl>,
+    entries: Vec<ModelDeclaration>,
+    stmt: &ast::Stmt,
+) -> Result<()> {
+    for (name, model, schema) in entries {
+        if models.contains_key(name.get()) {
+            return Err(CompileError::duplicate_entry(vec![name]));
+        }
+        models.insert(
+            name.get().clone(),
+            ModelDecl {
+                public: stmt.export,
+                model,
+                schema,
+            },
+        );
+    }
     Ok(())
 }
 
+// Imports the model that an import item names, if it names an exported model (since models are
+// only declared at the top level of a schema, the item's path must be a single name).
+//
+fn import_model(imported: &SchemaRef, item: &ast::ImportItem) -> Result<Option<ModelDeclaration>> {
+    let name = match item.path.as_slice() {
+        [name] => name,
+        _ => return Ok(None),
+    };
+    Ok(match imported.read()?.models.get(name.get()) {
+        Some(decl) if decl.public => Some((
+            item.name().clone(),
+            decl.model.clone(),
+            Some(decl.schema.clone().unwrap_or_else(|| imported.clone())),
+        )),
+        _ => None,
+    })
+}
+
+fn import_all_models(imported: &SchemaRef) -> Result<Vec<ModelDeclaration>> {
+    Ok(imported
+        .read()?
+        .models
+        .iter()
+        .filter(|(_, decl)| decl.public)
+        .map(|(name, decl)| {
+            (
+                Located::new(name.clone(), decl.model.name.location().clone()),
+                decl.model.clone(),
+                Some(decl.schema.clone().unwrap_or_else(|| imported.clone())),
+            )
+        })
+        .collect())
+}
+
+fn add_overloads(
+    overloads: &mut BTreeMap<Ident, Vec<Located<Decl<ExprEntry>>>>,
+    entries: Vec<Declaration<ExprEntry>>,
+    loc: &SourceLocation,
+    stmt: &ast::Stmt,
+) {
+    for (name, extern_, value, doc) in entries {
+        overloads
+            .entry(name.get().clone())
+            .or_default()
+            .push(Located::new(
+                Decl {
+                    public: stmt.export,
+                    extern_,
+                    fn_arg: false,
+                    name,
+                    value,
+                    doc,
+                },
+                loc.clone(),
+            ));
+    }
+}
+
+// Collects the doc comments on the fields of a record type.
+//
+fn field_docs(def: &ast::Type) -> BTreeMap<Ident, String> {
+    let mut docs = BTreeMap::new();
+    if let ast::TypeBody::Struct(entries) = &def.body {
+        for entry in entries {
+            if let ast::StructEntry::NameAndType(ast::NameAndType {
+                name,
+                doc: Some(doc),
+                ..
+            }) = entry
+            {
+                docs.insert(name.get().clone(), doc.clone());
+            }
+        }
+    }
+    docs
+}
+
 fn run_on_decl<E: Entry>(compiler: Compiler, ident: Located<Ident>, decl: &Decl<E>) -> Result<()> {
     let info = decl.value.run_on_info();
     match info {
@@ -1291,6 +2212,42 @@ pub fn unify_expr_decl(
     Ok(())
 }
 
+// Like unify_expr_decl, but for a function, which may be overloaded. The function's declaration
+// is the one declared at `name`, either in expr_decls (for the first overload) or in overloads.
+//
+fn unify_fn_decl(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    name: &Located<Ident>,
+    value: &STypedExpr,
+) -> Result<()> {
+    let s = schema.read()?;
+    let decl = match s.expr_decls.get(name.get()) {
+        Some(decl) if decl.name.location() == name.location() => decl,
+        _ => s
+            .overloads
+            .get(name.get())
+            .into_iter()
+            .flatten()
+            .find(|decl| decl.name.location() == name.location())
+            .ok_or_else(|| {
+                CompileError::internal(
+                    name.location().clone(),
+                    format!(
+                        "Could not find function declaration {} during reprocessing",
+                        name
+                    )
+                    .as_str(),
+                )
+            })?,
+    };
+
+    decl.value.unify(&value)?;
+    run_on_decl(compiler.clone(), decl.name.clone(), decl.get())?;
+
+    Ok(())
+}
+
 pub fn compile_schema_entries(
     compiler: Compiler,
     schema: Ref<Schema>,
@@ -1321,10 +2278,126 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
             let compiled = compile_expr(compiler.clone(), schema.clone(), expr)?;
             schema.write()?.exprs.push(Located::new(compiled, loc));
         }
+        ast::StmtBody::Test { name, body } => {
+            let compiled = compile_expr(compiler.clone(), schema.clone(), body)?;
+            compiled.type_.unify(&mkcref(MType::List(Located::new(
+                MType::new_unknown(format!("test {:?}", name.get()).as_str()),
+                name.location().clone(),
+            ))))?;
+            schema.write()?.tests.push(Located::new(
+                SchemaTest {
+                    kind: TestKind::ExpectEmpty {
+                        name: name.get().clone(),
+                    },
+                    expr: compiled,
+                },
+                loc,
+            ));
+        }
+        ast::StmtBody::Assert(expr) => {
+            let compiled = compile_expr(compiler.clone(), schema.clone(), expr)?;
+            compiled
+                .type_
+                .unify(&resolve_global_atom(compiler.clone(), "bool")?)?;
+            schema.write()?.tests.push(Located::new(
+                SchemaTest {
+                    kind: TestKind::Assert,
+                    expr: compiled,
+                },
+                loc,
+            ));
+        }
+        ast::StmtBody::Model(model) => {
+            // Compile a query of all of the model's own measures and dimensions, so that mistakes
+            // in them are reported even if the model is never queried.
+            //
+            let query = ast::ModelQuery {
+                model: model.name.clone(),
+                measures: model
+                    .measures
+                    .iter()
+                    .map(|f| vec![f.name.clone()])
+                    .collect(),
+                dimensions: model
+                    .dimensions
+                    .iter()
+                    .map(|f| vec![f.name.clone()])
+                    .collect(),
+            };
+            if query.measures.len() + query.dimensions.len() > 0 {
+                compile_model_query(compiler.clone(), schema.clone(), &loc, &query, false)?;
+            }
+        }
+        ast::StmtBody::Import {
+            path,
+            list,
+            args: Some(args),
+            ..
+        } => {
+            let imported = lookup_schema(compiler.clone(), schema.clone(), path)?;
+            let instance =
+                instantiate_schema(compiler.clone(), schema.clone(), imported.clone(), args)?;
+
+            let imported = imported.read()?.schema.clone();
+            let imported = imported.read()?;
+            bind_instance_decls(
+                compiler.clone(),
+                &imported.type_decls,
+                instance.clone(),
+                list,
+                |name, value| unify_type_decl(compiler.clone(), schema.clone(), name, value),
+            )?;
+            bind_instance_decls(
+                compiler.clone(),
+                &imported.expr_decls,
+                instance.clone(),
+                list,
+                |name, value| unify_expr_decl(compiler.clone(), schema.clone(), name, &value),
+            )?;
+            bind_instance_overloads(compiler.clone(), schema.clone(), instance, list)?;
+        }
         ast::StmtBody::Import { .. } => {}
-        ast::StmtBody::TypeDef(nt) => {
-            let type_ = resolve_type(compiler.clone(), schema.clone(), &nt.def)?;
-            unify_type_decl(compiler.clone(), schema.clone(), &nt.name, type_)?;
+        ast::StmtBody::TypeDef {
+            name,
+            generics,
+            def,
+        } => {
+            let type_ = if generics.is_empty() {
+                resolve_type(compiler.clone(), schema.clone(), def)?
+            } else {
+                // The body of a generic type is resolved within its own scope, where each variable
+                // is a type name that is substituted when the type is instantiated.
+                let inner_schema =
+                    Schema::new(schema.read()?.file.clone(), schema.read()?.folder.clone());
+                inner_schema.write()?.parent_scope = Some(schema.clone());
+
+                for generic in generics {
+                    if inner_schema.read()?.type_decls.get(generic).is_some() {
+                        return Err(CompileError::duplicate_entry(vec![generic.clone()]));
+                    }
+                    inner_schema.write()?.type_decls.insert(
+                        generic.get().clone(),
+                        Located::new(
+                            Decl {
+                                public: false,
+                                extern_: true,
+                                fn_arg: false,
+                                name: generic.clone(),
+                                value: mkcref(MType::Name(generic.clone())),
+                                doc: Doc::default(),
+                            },
+                            loc.clone(),
+                        ),
+                    );
+                }
+
+                let body = resolve_type(compiler.clone(), inner_schema.clone(), def)?;
+                mkcref(MType::Generic(Located::new(
+                    UserGenericType::new(name.clone(), generics.clone(), body),
+                    loc.clone(),
+                )))
+            };
+            unify_type_decl(compiler.clone(), schema.clone(), name, type_)?;
         }
         ast::StmtBody::FnDef {
             name,
@@ -1332,7 +2405,17 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
             args,
             ret,
             body,
+            aggregate,
+            bounds,
         } => {
+            let aggregate = *aggregate;
+            if aggregate && matches!(body, ast::FnBody::Native) {
+                return Err(CompileError::unimplemented(
+                    loc.clone(),
+                    "native aggregate functions",
+                ));
+            }
+
             let inner_schema =
                 Schema::new(schema.read()?.file.clone(), schema.read()?.folder.clone());
             inner_schema.write()?.parent_scope = Some(schema.clone());
@@ -1348,12 +2431,31 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                             fn_arg: true,
                             name: generic.clone(),
                             value: mkcref(MType::Name(generic.clone())),
+                            doc: Doc::default(),
                         },
                         loc.clone(),
                     ),
                 );
                 unknowns.insert(generic.get().clone(), CRef::new_unknown(generic.as_str()));
             }
+
+            // Within the body, a bounded generic is known to be (at least) its bound, so the body
+            // can reference the bound's fields. Callers are checked against the bound when the
+            // function's type is instantiated.
+            //
+            let mut compiled_bounds = BTreeMap::new();
+            for (generic, bound) in bounds {
+                if !unknowns.contains_key(generic) {
+                    return Err(CompileError::no_such_entry(vec![Ident::with_location(
+                        loc.clone(),
+                        generic.clone(),
+                    )]));
+                }
+                let bound = resolve_bound(compiler.clone(), schema.clone(), bound)?;
+                unknowns.insert(generic.clone(), bound.type_.clone());
+                compiled_bounds.insert(generic.clone(), bound);
+            }
+
             let mut compiled_args = Vec::new();
             for arg in args {
                 if inner_schema.read()?.expr_decls.get(&arg.name).is_some() {
@@ -1374,6 +2476,7 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                                 type_: stype.clone(),
                                 expr: mkcref(Expr::ContextRef(arg.name.get().clone())),
                             },
+                            doc: Doc::default(),
                         },
                         loc.clone(),
                     ),
@@ -1389,7 +2492,26 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                     .write()?
                     .externs
                     .insert(arg.name.get().clone(), type_.clone());
-                compiled_args.push(MField::new_nullable(arg.name.get().clone(), type_.clone()));
+                if let Some(default) = &arg.default {
+                    if matches!(body, ast::FnBody::Native) {
+                        return Err(CompileError::unimplemented(
+                            arg.name.location().clone(),
+                            "default arguments for native functions",
+                        ));
+                    }
+                    let default = compile_expr(compiler.clone(), schema.clone(), default)?;
+                    substituted.unify(&default.type_)?;
+                    inner_schema
+                        .write()?
+                        .arg_defaults
+                        .insert(arg.name.get().clone(), default);
+                }
+                compiled_args.push(MField {
+                    name: arg.name.get().clone(),
+                    type_: type_.clone(),
+                    nullable: arg.nullable,
+                    loc: arg.name.location().clone(),
+                });
             }
 
             let (compiled, is_sql) = match body {
@@ -1416,37 +2538,71 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                     },
                     true,
                 ),
+                // The body of an aggregate function is itself an aggregate context, so it may call
+                // other aggregates.
+                ast::FnBody::Expr(expr) if aggregate => (
+                    compile_scoped_expr(
+                        compiler.clone(),
+                        inner_schema.clone(),
+                        SQLScope::empty().read()?.with_aggregates(),
+                        expr,
+                    )?,
+                    false,
+                ),
+                // The body of a plain function is not an aggregate context, even if the function is
+                // only called where aggregates are allowed, so a function that calls aggregates must
+                // be declared as an `agg fn` itself.
                 ast::FnBody::Expr(expr) => (
                     compile_expr(compiler.clone(), inner_schema.clone(), expr)?,
                     false,
                 ),
             };
 
-            if let Some(ret) = ret {
-                let ret = resolve_type(compiler.clone(), inner_schema.clone(), ret)?;
-                let ret = ret.substitute(&unknowns)?;
-                ret.unify(&compiled.type_)?
-            }
+            // Bounded generics are the bound within the body, but are kept by name in the
+            // function's type, so that e.g. a function declared to return `[R]` returns the
+            // caller's records (with all of their fields) rather than just the bound's fields.
+            //
+            let ret_type = match ret {
+                Some(ret) => {
+                    let ret = resolve_type(compiler.clone(), inner_schema.clone(), ret)?;
+                    ret.substitute(&unknowns)?.unify(&compiled.type_)?;
+                    if compiled_bounds.is_empty() {
+                        compiled.type_.clone()
+                    } else {
+                        ret
+                    }
+                }
+                None => compiled.type_.clone(),
+            };
 
             for generic in generics {
+                if compiled_bounds.contains_key(generic.get()) {
+                    continue;
+                }
                 unknowns
                     .get(&generic)
                     .unwrap()
                     .unify(&mkcref(MType::Name(generic.clone())))?;
             }
 
+            let arg_names = compiled_args
+                .iter()
+                .map(|a| a.name.clone())
+                .collect::<Vec<_>>();
             let fn_type = SType::new_poly(
                 mkcref(MType::Fn(Located::new(
                     MFnType {
                         args: compiled_args,
-                        ret: compiled.type_.clone(),
+                        ret: ret_type,
+                        anonymous: false,
                     },
                     loc,
                 ))),
                 BTreeSet::from_iter(generics.to_idents().into_iter()),
+                compiled_bounds,
             );
 
-            unify_expr_decl(
+            unify_fn_decl(
                 compiler.clone(),
                 schema.clone(),
                 name,
@@ -1458,6 +2614,8 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
                             Expr::NativeFn(..) => expr.clone(),
                             _ => Expr::Fn(FnExpr {
                                 inner_schema: inner_schema.clone(),
+                                args: arg_names.clone(),
+                                aggregate,
                                 body: if is_sql {
                                     FnBody::SQLBuiltin
                                 } else {
@@ -1477,24 +2635,43 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
             };
             let compiled = compile_expr(compiler.clone(), schema.clone(), &body)?;
             lhs_type.unify(&compiled.type_)?;
+
+            // The declared type must accept the value's nullability, which is only known once
+            // the value's type is.
+            //
+            let expr = if type_.is_some() {
+                let lhs_type = lhs_type.clone();
+                let expr = compiled.expr;
+                compiler.async_cref(async move {
+                    compiled.type_.clone().await?;
+                    compiled.type_.assignable_to(&lhs_type)?;
+                    Ok(expr)
+                })?
+            } else {
+                compiled.expr
+            };
             unify_expr_decl(
                 compiler.clone(),
                 schema.clone(),
                 name,
                 &STypedExpr {
                     type_: SType::new_mono(lhs_type),
-                    expr: compiled.expr,
+                    expr,
                 },
             )?;
         }
         ast::StmtBody::Extern { name, type_ } => {
+            let type_ = resolve_type(compiler.clone(), schema.clone(), type_)?;
+
+            let expr = extern_value(&schema, name, &type_)?;
+
             unify_expr_decl(
                 compiler.clone(),
                 schema.clone(),
                 name,
                 &STypedExpr {
-                    type_: SType::new_mono(resolve_type(compiler.clone(), schema.clone(), type_)?),
-                    expr: mkcref(Expr::Unknown),
+                    type_: SType::new_mono(type_),
+                    expr,
                 },
             )?;
         }
@@ -1503,19 +2680,63 @@ fn compile_schema_entry(compiler: &Compiler, schema: &Ref<Schema>, stmt: &ast::S
     Ok(())
 }
 
+// If the schema was instantiated by a parameterized import, then an extern is bound to the
+// importer's argument, which must match the declared type. Otherwise, its value is supplied at
+// runtime through the context.
+//
+pub fn extern_value(
+    schema: &Ref<Schema>,
+    name: &Located<Ident>,
+    type_: &CRef<MType>,
+) -> Result<CRef<Expr<CRef<MType>>>> {
+    let arg = schema.read()?.extern_args.get(name.get()).cloned();
+    Ok(match arg {
+        Some(arg) => {
+            type_.unify(&arg.type_)?;
+            arg.expr
+        }
+        None => mkcref(Expr::ContextRef(extern_context_key(
+            &schema.read()?.file,
+            name.get(),
+        ))),
+    })
+}
+
+// The context key that an extern's value is supplied under at runtime. Function arguments are
+// also context values, so the key is namespaced (and qualified by the declaring schema's file)
+// to keep an argument, or another schema's extern, with the same name from capturing it.
+//
+pub fn extern_context_key(file: &str, name: &Ident) -> Ident {
+    format!("{}{}{}:{}", QS_NAMESPACE, EXTERN_KEY_PREFIX, name, file).into()
+}
+
+// The name of the extern whose value is supplied under the given context key, if it is one.
+//
+pub fn extern_context_name(key: &Ident) -> Option<&str> {
+    key.as_str()
+        .strip_prefix(QS_NAMESPACE)?
+        .strip_prefix(EXTERN_KEY_PREFIX)?
+        .split_once(':')
+        .map(|(name, _)| name)
+}
+
+const EXTERN_KEY_PREFIX: &str = "extern:";
+
 pub fn gather_schema_externs(schema: Ref<Schema>) -> Result<()> {
-    let s = schema.read()?;
-    for (name, decl) in &s.expr_decls {
+    let mut externs = BTreeMap::new();
+    for (name, decl) in &schema.read()?.expr_decls {
         if decl.extern_ {
             let e = &decl.value;
-
-            schema.write()?.externs.insert(
+            let loc = decl.name.location().clone();
+            externs.insert(
                 name.clone(),
-                e.type_.then(|t: Ref<SType>| Ok(t.read()?.instantiate()?))?,
+                e.type_
+                    .then(move |t: Ref<SType>| Ok(t.read()?.instantiate(&loc)?))?,
             );
         }
     }
 
+    schema.write()?.externs.extend(externs);
     Ok(())
 }
 
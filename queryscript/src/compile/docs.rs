@@ -0,0 +1,95 @@
+use snafu::prelude::*;
+
+use crate::ast::Pretty;
+use crate::compile::error::*;
+use crate::compile::schema::*;
+
+// Generates a Markdown reference for the types, functions, and values declared in a schema,
+// including their doc comments. Declarations imported from other schemas are left out, since
+// they're documented in their own schema's reference.
+//
+pub fn schema_reference(schema: &Schema) -> Result<String> {
+    let mut sections = vec![format!("# {}", schema.file)];
+
+    let types = schema
+        .type_decls
+        .values()
+        .filter(|decl| is_local(schema, decl))
+        .collect::<Vec<_>>();
+    if types.len() > 0 {
+        sections.push("## Types".to_string());
+    }
+    for decl in types {
+        let mut section = vec![format!(
+            "### `{}`{}",
+            decl.name,
+            if decl.public { "" } else { " (private)" }
+        )];
+        if let Some(text) = &decl.doc.text {
+            section.push(text.clone());
+        }
+        let type_ = decl.value.must().context(RuntimeSnafu {
+            loc: decl.location().clone(),
+        })?;
+        section.push(format!("```\n{}\n```", type_.read()?.pretty()));
+        if decl.doc.fields.len() > 0 {
+            section.push(
+                decl.doc
+                    .fields
+                    .iter()
+                    .map(|(name, text)| format!("- `{}`: {}", name, text.replace("\n", " ")))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        sections.push(section.join("\n\n"));
+    }
+
+    let exprs = schema
+        .expr_decls
+        .values()
+        .filter(|decl| is_local(schema, decl))
+        .collect::<Vec<_>>();
+    if exprs.len() > 0 {
+        sections.push("## Values and functions".to_string());
+    }
+    for decl in exprs {
+        // Each overload of a function is documented separately.
+        //
+        let overloads = schema.overloads.get(decl.name.get()).into_iter().flatten();
+        for decl in std::iter::once(decl).chain(overloads) {
+            let mut section = vec![format!(
+                "### `{}`{}",
+                decl.name,
+                if decl.extern_ {
+                    " (extern)"
+                } else if decl.public {
+                    ""
+                } else {
+                    " (private)"
+                }
+            )];
+            if let Some(text) = &decl.doc.text {
+                section.push(text.clone());
+            }
+            section.push(format!(
+                "```\n{}\n```",
+                decl.value
+                    .type_
+                    .must()
+                    .context(RuntimeSnafu {
+                        loc: decl.location().clone(),
+                    })?
+                    .read()?
+                    .pretty()
+            ));
+            sections.push(section.join("\n\n"));
+        }
+    }
+
+    Ok(sections.join("\n\n") + "\n")
+}
+
+fn is_local<E: Clone>(schema: &Schema, decl: &Located<Decl<E>>) -> bool {
+    !decl.fn_arg && decl.location().file().as_ref() == Some(&schema.file)
+}
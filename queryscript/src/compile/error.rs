@@ -1,5 +1,5 @@
 use crate::ast;
-use crate::ast::{Pretty, Range};
+use crate::ast::{Located, Pretty, Range};
 use crate::compile::schema::MType;
 use crate::error::MultiError;
 pub use crate::parser::error::ErrorLocation;
@@ -80,6 +80,12 @@ pub enum CompileError {
         backtrace: Option<Backtrace>,
     },
 
+    #[snafu(display("Private declaration: {} is not exported", path.pretty()))]
+    PrivateDecl {
+        path: ast::Path,
+        backtrace: Option<Backtrace>,
+    },
+
     #[snafu(display(
         "Wrong kind: expected {} declaration at {}, found {}",
         expected.white().bold(),
@@ -108,6 +114,68 @@ pub enum CompileError {
         loc: ErrorLocation,
     },
 
+    #[snafu(display(
+        "Invalid enum member: {} is not one of {}",
+        format!("'{}'", value).white().bold(),
+        members.iter().map(|m| format!("'{}'", m)).collect::<Vec<_>>().join(", "),
+    ))]
+    EnumMemberError {
+        value: String,
+        members: Vec<String>,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
+    #[snafu(display("Invalid arguments for type {}: {}", name.white().bold(), what))]
+    TypeArgsError {
+        name: String,
+        what: String,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
+    #[snafu(display("Cannot resolve overloaded function {}: {}", path.pretty(), what))]
+    OverloadError {
+        path: ast::Path,
+        what: String,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
+    #[snafu(display(
+        "Aggregate function {} can only be called in a SELECT list, HAVING, ORDER BY, or the \
+         body of an agg fn",
+        path.pretty()
+    ))]
+    AggregateContextError {
+        path: ast::Path,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
+    #[snafu(display("{} requires inlining, which is disabled", what))]
+    InliningRequiredError {
+        what: String,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
+    #[snafu(display("Type does not satisfy the bound on {}: {}", name.white().bold(), what))]
+    GenericBoundError {
+        name: String,
+        what: String,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
+    #[snafu(display("Invalid query of model {}: {}", model.white().bold(), what))]
+    ModelQueryError {
+        model: String,
+        what: String,
+        backtrace: Option<Backtrace>,
+        loc: ErrorLocation,
+    },
+
     #[snafu(display("Error importing {}: {}", path.pretty(), what))]
     ImportError {
         path: ast::Path,
@@ -115,6 +183,15 @@ pub enum CompileError {
         backtrace: Option<Backtrace>,
     },
 
+    #[snafu(display("Cyclic import: {}", pretty_import_chain(chain)))]
+    ImportCycle {
+        // Each file in the cycle, located at the import statement that imports it. The first
+        // entry is the file that starts the chain.
+        //
+        chain: Vec<Located<String>>,
+        backtrace: Option<Backtrace>,
+    },
+
     #[snafu(display("Invalid scalar subselect: {}", what))]
     ScalarSubselectError {
         what: String,
@@ -147,6 +224,10 @@ impl CompileError {
         return DuplicateEntrySnafu { path }.build();
     }
 
+    pub fn private_decl(path: ast::Path) -> CompileError {
+        return PrivateDeclSnafu { path }.build();
+    }
+
     pub fn wrong_kind(path: ast::Path, expected: &str, kind: &str) -> CompileError {
         return WrongKindSnafu {
             path,
@@ -173,6 +254,63 @@ impl CompileError {
         .build();
     }
 
+    pub fn enum_member(loc: ErrorLocation, value: &str, members: &Vec<String>) -> CompileError {
+        return EnumMemberSnafu {
+            loc,
+            value: value.to_string(),
+            members: members.clone(),
+        }
+        .build();
+    }
+
+    pub fn type_args(loc: ErrorLocation, name: &str, what: &str) -> CompileError {
+        return TypeArgsSnafu {
+            loc,
+            name: name.to_string(),
+            what: what.to_string(),
+        }
+        .build();
+    }
+
+    pub fn overload(loc: ErrorLocation, path: ast::Path, what: &str) -> CompileError {
+        return OverloadSnafu {
+            loc,
+            path,
+            what: what.to_string(),
+        }
+        .build();
+    }
+
+    pub fn aggregate_context(loc: ErrorLocation, path: ast::Path) -> CompileError {
+        return AggregateContextSnafu { loc, path }.build();
+    }
+
+    pub fn inlining_required(loc: ErrorLocation, what: &str) -> CompileError {
+        return InliningRequiredSnafu {
+            loc,
+            what: what.to_string(),
+        }
+        .build();
+    }
+
+    pub fn generic_bound(loc: ErrorLocation, name: &ast::Ident, what: &str) -> CompileError {
+        return GenericBoundSnafu {
+            loc,
+            name: name.to_string(),
+            what: what.to_string(),
+        }
+        .build();
+    }
+
+    pub fn model_query(loc: ErrorLocation, model: &ast::Ident, what: &str) -> CompileError {
+        return ModelQuerySnafu {
+            loc,
+            model: model.to_string(),
+            what: what.to_string(),
+        }
+        .build();
+    }
+
     pub fn import_error(path: ast::Path, what: &str) -> CompileError {
         return ImportSnafu {
             path,
@@ -181,6 +319,10 @@ impl CompileError {
         .build();
     }
 
+    pub fn import_cycle(chain: Vec<Located<String>>) -> CompileError {
+        return ImportCycleSnafu { chain }.build();
+    }
+
     pub fn scalar_subselect(loc: ErrorLocation, what: &str) -> CompileError {
         return ScalarSubselectSnafu {
             loc,
@@ -241,6 +383,21 @@ pub fn path_location(path: &Vec<ast::Located<ast::Ident>>) -> ErrorLocation {
     )
 }
 
+fn pretty_import_chain(chain: &Vec<Located<String>>) -> String {
+    let mut ret = chain[0].get().white().bold().to_string();
+    for file in &chain[1..] {
+        ret.push_str(
+            format!(
+                " -> {} (imported at {})",
+                file.get().white().bold(),
+                file.location().pretty()
+            )
+            .as_str(),
+        );
+    }
+    ret
+}
+
 impl PrettyError for CompileError {
     fn location(&self) -> ErrorLocation {
         match self {
@@ -254,10 +411,19 @@ impl PrettyError for CompileError {
             CompileError::MissingArg { path, .. } => path_location(path),
             CompileError::DuplicateEntry { path, .. } => path_location(path),
             CompileError::NoSuchEntry { path, .. } => path_location(path),
+            CompileError::PrivateDecl { path, .. } => path_location(path),
             CompileError::WrongKind { path, .. } => path_location(path),
             CompileError::WrongType { lhs, .. } => lhs.location(),
             CompileError::CoercionError { loc, .. } => loc.clone(),
+            CompileError::EnumMemberError { loc, .. } => loc.clone(),
+            CompileError::TypeArgsError { loc, .. } => loc.clone(),
+            CompileError::OverloadError { loc, .. } => loc.clone(),
+            CompileError::AggregateContextError { loc, .. } => loc.clone(),
+            CompileError::InliningRequiredError { loc, .. } => loc.clone(),
+            CompileError::GenericBoundError { loc, .. } => loc.clone(),
+            CompileError::ModelQueryError { loc, .. } => loc.clone(),
             CompileError::ImportError { path, .. } => path_location(path),
+            CompileError::ImportCycle { chain, .. } => chain.last().unwrap().location().clone(),
             CompileError::ScalarSubselectError { loc, .. } => loc.clone(),
             CompileError::Multiple { sources } => sources.first().unwrap().location(),
         }
@@ -1,7 +1,7 @@
 use lazy_static::lazy_static;
 use snafu::prelude::*;
 use std::any::Any;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::sync::Arc;
 
@@ -13,7 +13,7 @@ use super::{
     sql::get_rowtype,
     Compiler,
 };
-use crate::ast::SourceLocation;
+use crate::ast::{Located, SourceLocation};
 use crate::runtime;
 use crate::types::{AtomicType, Type};
 
@@ -72,6 +72,7 @@ fn validate_args(
 lazy_static! {
     pub static ref SUM_GENERIC_NAME: Ident = "SumAgg".into();
     pub static ref EXTERNAL_GENERIC_NAME: Ident = "External".into();
+    pub static ref EXCLUDE_GENERIC_NAME: Ident = "Exclude".into();
     pub static ref GLOBAL_GENERICS: BTreeMap<Ident, Box<dyn GenericFactory>> = [
         BuiltinGeneric::<SumGeneric>::constructor(),
         BuiltinGeneric::<ExternalType>::constructor(),
@@ -236,6 +237,328 @@ impl Generic for ExternalType {
     }
 }
 
+// ExcludeType represents `T EXCLUDE (a, b)` where `T` is not yet known, e.g. because it's a type
+// variable in a generic function's signature. Once the inner type is bound to a record (or a
+// relation of records), it behaves like that type without the excluded fields.
+pub struct ExcludeType {
+    loc: SourceLocation,
+    inner: CRef<MType>,
+    excluded: Vec<Located<Ident>>,
+}
+
+impl ExcludeType {
+    pub fn new(
+        loc: &SourceLocation,
+        inner: CRef<MType>,
+        excluded: Vec<Located<Ident>>,
+    ) -> Arc<dyn Generic> {
+        Arc::new(ExcludeType {
+            loc: loc.clone(),
+            inner,
+            excluded,
+        })
+    }
+
+    fn excluded_names(&self) -> BTreeSet<Ident> {
+        self.excluded.iter().map(|e| e.get().clone()).collect()
+    }
+
+    fn resolve(&self) -> Result<CRef<MType>> {
+        let loc = self.loc.clone();
+        let excluded = self.excluded.clone();
+        self.inner
+            .then(move |inner: Ref<MType>| match &*inner.read()? {
+                MType::Name(name) => Err(CompileError::internal(
+                    name.location().clone(),
+                    format!("Encountered free type variable: {}", name.get()).as_str(),
+                )),
+                inner => exclude_fields(&loc, inner, &excluded),
+            })
+    }
+}
+
+// Removes the excluded fields from a record type, or from the rows of a relation type. Every
+// excluded field must exist in the record.
+pub fn exclude_fields(
+    loc: &SourceLocation,
+    type_: &MType,
+    excluded: &Vec<Located<Ident>>,
+) -> Result<CRef<MType>> {
+    match type_ {
+        MType::Record(fields) => {
+            for name in excluded {
+                if !fields.iter().any(|f| &f.name == name.get()) {
+                    return Err(CompileError::no_such_entry(vec![name.clone()]));
+                }
+            }
+
+            Ok(mkcref(MType::Record(Located::new(
+                fields
+                    .iter()
+                    .filter(|f| !excluded.iter().any(|e| e.get() == &f.name))
+                    .cloned()
+                    .collect(),
+                loc.clone(),
+            ))))
+        }
+        MType::List(inner) => {
+            let row_loc = loc.clone();
+            let excluded = excluded.clone();
+            Ok(mkcref(MType::List(Located::new(
+                inner.get().then(move |row: Ref<MType>| {
+                    exclude_fields(&row_loc, &*row.read()?, &excluded)
+                })?,
+                loc.clone(),
+            ))))
+        }
+        MType::Name(_) => Ok(mkcref(MType::Generic(Located::new(
+            ExcludeType::new(loc, mkcref(type_.clone()), excluded.clone()),
+            loc.clone(),
+        )))),
+        MType::Generic(generic) => match as_generic::<ExternalType>(generic.get().as_ref()) {
+            Some(external) => {
+                let loc = loc.clone();
+                let excluded = excluded.clone();
+                external
+                    .inner_type()
+                    .then(move |inner: Ref<MType>| exclude_fields(&loc, &*inner.read()?, &excluded))
+            }
+            None => Ok(mkcref(MType::Generic(Located::new(
+                ExcludeType::new(loc, mkcref(type_.clone()), excluded.clone()),
+                loc.clone(),
+            )))),
+        },
+        _ => Err(CompileError::wrong_type(
+            &MType::Record(Located::new(Vec::new(), loc.clone())),
+            type_,
+        )),
+    }
+}
+
+fn exclude_runtime_fields(type_: Type, excluded: &BTreeSet<Ident>) -> runtime::error::Result<Type> {
+    match type_ {
+        Type::Record(fields) => Ok(Type::Record(
+            fields
+                .into_iter()
+                .filter(|f| !excluded.contains(&f.name))
+                .collect(),
+        )),
+        Type::List(inner) => Ok(Type::List(Box::new(exclude_runtime_fields(
+            *inner, excluded,
+        )?))),
+        _ => runtime::error::fail!("Cannot exclude fields from {:?}", type_),
+    }
+}
+
+impl std::fmt::Debug for ExcludeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        debug_fmt_generic(f, &EXCLUDE_GENERIC_NAME, &self.inner)?;
+        write!(
+            f,
+            "({})",
+            self.excluded
+                .iter()
+                .map(|e| e.get().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Generic for ExcludeType {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &Ident {
+        &EXCLUDE_GENERIC_NAME
+    }
+
+    fn to_runtime_type(&self) -> runtime::error::Result<Type> {
+        exclude_runtime_fields(
+            self.inner.must()?.read()?.to_runtime_type()?,
+            &self.excluded_names(),
+        )
+    }
+
+    fn substitute(&self, variables: &BTreeMap<Ident, CRef<MType>>) -> Result<Arc<dyn Generic>> {
+        Ok(ExcludeType::new(
+            &self.loc,
+            self.inner.substitute(variables)?,
+            self.excluded.clone(),
+        ))
+    }
+
+    fn unify(&self, other: &MType) -> Result<()> {
+        if let MType::Generic(other_inner) = other {
+            if let Some(other) = as_generic::<Self>(other_inner.get().as_ref()) {
+                if self.excluded_names() == other.excluded_names() {
+                    return self.inner.unify(&other.inner);
+                }
+            }
+        }
+
+        // If the inner type is a free generic (e.g. `R` in `[R] EXCLUDE active`), then it's
+        // inferred from the other type: it must have all of the other type's fields, plus the
+        // excluded ones (whose types are only known if something else constrains them).
+        //
+        if !self.inner.is_known()? {
+            return match other {
+                MType::Record(fields) => {
+                    for name in &self.excluded {
+                        if fields.iter().any(|f| &f.name == name.get()) {
+                            return Err(CompileError::wrong_type(
+                                &MType::Generic(Located::new(
+                                    ExcludeType::new(
+                                        &self.loc,
+                                        self.inner.clone(),
+                                        self.excluded.clone(),
+                                    ),
+                                    self.loc.clone(),
+                                )),
+                                other,
+                            ));
+                        }
+                    }
+
+                    let mut inner_fields = fields.get().clone();
+                    inner_fields.extend(self.excluded.iter().map(|name| MField {
+                        loc: name.location().clone(),
+                        ..MField::new_nullable(
+                            name.get().clone(),
+                            MType::new_unknown(name.get().as_str()),
+                        )
+                    }));
+                    self.inner.unify(&mkcref(MType::Record(Located::new(
+                        inner_fields,
+                        fields.location().clone(),
+                    ))))
+                }
+                MType::List(rows) => {
+                    let inner_row = MType::new_unknown("excluded row");
+                    self.inner.unify(&mkcref(MType::List(Located::new(
+                        inner_row.clone(),
+                        rows.location().clone(),
+                    ))))?;
+                    mkcref(MType::Generic(Located::new(
+                        ExcludeType::new(&self.loc, inner_row, self.excluded.clone()),
+                        self.loc.clone(),
+                    )))
+                    .unify(rows.get())
+                }
+                _ => self.resolve()?.unify(&mkcref(other.clone())),
+            };
+        }
+
+        self.resolve()?.unify(&mkcref(other.clone()))
+    }
+
+    fn get_rowtype(&self, compiler: Compiler) -> Result<Option<CRef<MType>>> {
+        Ok(Some(get_rowtype(compiler, self.resolve()?)?))
+    }
+}
+
+// UserGenericType is a generic type declared in QueryScript, e.g. `type Paged<R> { items [R] }`.
+// It's stored as the value of the type's declaration, and instantiating it substitutes the
+// arguments for its variables in the type's body.
+pub struct UserGenericType {
+    name: Located<Ident>,
+    variables: Vec<Located<Ident>>,
+    body: CRef<MType>,
+}
+
+impl UserGenericType {
+    pub fn new(
+        name: Located<Ident>,
+        variables: Vec<Located<Ident>>,
+        body: CRef<MType>,
+    ) -> Arc<dyn Generic> {
+        Arc::new(UserGenericType {
+            name,
+            variables,
+            body,
+        })
+    }
+
+    pub fn instantiate(&self, loc: &SourceLocation, args: Vec<CRef<MType>>) -> Result<CRef<MType>> {
+        validate_args(loc, &args, self.variables.len(), self.name.get())?;
+
+        let variables = self
+            .variables
+            .iter()
+            .map(|v| v.get().clone())
+            .zip(args.into_iter())
+            .collect::<BTreeMap<_, _>>();
+
+        self.body
+            .then(move |body: Ref<MType>| body.read()?.substitute(&variables))
+    }
+}
+
+impl std::fmt::Debug for UserGenericType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}<{}> ",
+            self.name.get(),
+            self.variables
+                .iter()
+                .map(|v| v.get().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        std::fmt::Debug::fmt(&self.body, f)
+    }
+}
+
+impl Generic for UserGenericType {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &Ident {
+        self.name.get()
+    }
+
+    fn to_runtime_type(&self) -> runtime::error::Result<Type> {
+        runtime::error::fail!(
+            "Generic type {} must be instantiated with {} argument(s)",
+            self.name.get(),
+            self.variables.len()
+        )
+    }
+
+    fn substitute(&self, _variables: &BTreeMap<Ident, CRef<MType>>) -> Result<Arc<dyn Generic>> {
+        // Generic types are declared at the top level of a schema, so they cannot refer to any
+        // variables other than their own.
+        Ok(UserGenericType::new(
+            self.name.clone(),
+            self.variables.clone(),
+            self.body.clone(),
+        ))
+    }
+
+    fn unify(&self, other: &MType) -> Result<()> {
+        if let MType::Generic(other_inner) = other {
+            if let Some(other) = as_generic::<Self>(other_inner.get().as_ref()) {
+                if self.name.get() == other.name.get()
+                    && self.name.location() == other.name.location()
+                {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(CompileError::wrong_type(
+            &MType::Generic(Located::new(
+                UserGenericType::new(self.name.clone(), self.variables.clone(), self.body.clone()),
+                self.name.location().clone(),
+            )),
+            other,
+        ))
+    }
+}
+
 pub trait GenericFactory: Send + Sync {
     fn new(&self, loc: &SourceLocation, args: Vec<CRef<MType>>) -> Result<Arc<dyn Generic>>;
     fn name(&self) -> &Ident;
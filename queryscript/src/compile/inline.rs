@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use sqlparser::ast as sqlast;
 
+use crate::ast::SourceLocation;
 use crate::compile::error::*;
 use crate::compile::schema::*;
 use crate::compile::traverse::{SQLVisitor, Visit, VisitSQL, Visitor};
@@ -25,6 +26,11 @@ impl Visitor<CRef<MType>> for ContextInliner {
                     None
                 }
             }
+            // Function values (e.g. a function passed as an argument) bind their own arguments,
+            // so context refs in their bodies must not be captured by the context being inlined.
+            // Their arguments are substituted when they're called (see ParamInliner).
+            //
+            Expr::Fn(..) => Some(expr.clone()),
             _ => None,
         })
     }
@@ -40,6 +46,9 @@ pub async fn inline_context(
 
 pub struct ParamInliner {
     context: BTreeMap<Ident, SQLBody>,
+
+    // The location of the call being inlined, which errors in the inlined body are reported at.
+    loc: SourceLocation,
 }
 
 impl SQLVisitor for ParamInliner {
@@ -100,8 +109,17 @@ impl Visitor<CRef<MType>> for ParamInliner {
                 );
                 let mut context = BTreeMap::new();
                 for (name, param) in params {
-                    let expr = inline_params(param.expr.unwrap_schema_entry().await?).await?;
+                    let is_call = matches!(param.expr.as_ref(), Expr::FnCall(..));
+                    let expr =
+                        inline_params(param.expr.unwrap_schema_entry().await?, &self.loc).await?;
                     match expr.as_ref() {
+                        // The names referenced by the arguments of an inlined function call were
+                        // already tracked when the call was compiled, and may be bound by this
+                        // SQL, so only its params are carried over.
+                        Expr::SQL(sql) if is_call => {
+                            names.params.extend(sql.names.params.clone());
+                            context.insert(name.clone(), sql.body.clone());
+                        }
                         Expr::SQL(sql) => {
                             names.extend(sql.names.clone());
                             context.insert(name.clone(), sql.body.clone());
@@ -118,18 +136,54 @@ impl Visitor<CRef<MType>> for ParamInliner {
                     }
                 }
 
-                let visitor = ParamInliner { context };
+                let visitor = ParamInliner {
+                    context,
+                    loc: self.loc.clone(),
+                };
                 let body = body.visit_sql(&visitor);
                 Some(Expr::SQL(Arc::new(SQL { names, body })))
             }
+            // Calls to functions that were passed as arguments are inlined once the function is
+            // known, just like calls to named functions.
+            Expr::FnCall(FnCallExpr { func, args, .. }) => {
+                match func.expr.unwrap_schema_entry().await?.as_ref() {
+                    Expr::Fn(FnExpr {
+                        body: FnBody::Expr(body),
+                        args: arg_names,
+                        ..
+                    }) => {
+                        let context = arg_names
+                            .iter()
+                            .cloned()
+                            .zip(args.iter().map(|a| a.expr.clone()))
+                            .collect();
+                        let body = inline_context(body.clone(), context).await?;
+                        Some(inline_params(body, &self.loc).await?.as_ref().clone())
+                    }
+                    Expr::Fn(FnExpr {
+                        body: FnBody::SQLBuiltin,
+                        ..
+                    }) => {
+                        return Err(CompileError::unimplemented(
+                            self.loc.clone(),
+                            "calling builtin SQL functions passed as arguments",
+                        ))
+                    }
+                    _ => None,
+                }
+            }
             _ => None,
         })
     }
 }
 
-pub async fn inline_params(expr: Arc<Expr<CRef<MType>>>) -> Result<Arc<Expr<CRef<MType>>>> {
+pub async fn inline_params(
+    expr: Arc<Expr<CRef<MType>>>,
+    loc: &SourceLocation,
+) -> Result<Arc<Expr<CRef<MType>>>> {
     let visitor = ParamInliner {
         context: BTreeMap::new(),
+        loc: loc.clone(),
     };
     Ok(Arc::new(expr.visit(&visitor).await?))
 }
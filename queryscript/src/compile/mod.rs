@@ -2,10 +2,12 @@ pub mod autocomplete;
 mod builtin_types;
 mod coerce;
 pub mod compile;
+pub mod docs;
 pub mod error;
 mod generics;
 pub mod inference;
 pub mod inline;
+pub mod model;
 pub mod schema;
 mod scope;
 pub mod sql;
@@ -14,7 +16,8 @@ mod unsafe_expr;
 mod util;
 
 pub use compile::{
-    lookup_path, lookup_schema, Compiler, CompilerConfig, OnSchema, OnSymbol, SymbolKind,
+    extern_context_key, extern_context_name, lookup_path, lookup_schema, Compiler, CompilerConfig,
+    OnSchema, OnSymbol, SymbolKind,
 };
 pub use error::{CompileError, Result};
 pub use schema::{mkref, Schema, SchemaRef};
@@ -0,0 +1,379 @@
+use sqlparser::ast as sqlast;
+use std::collections::BTreeSet;
+
+use crate::ast;
+use crate::ast::{Ident, Located, SourceLocation, ToSqlIdent};
+use crate::compile::compile::{compile_query_expr, Compiler};
+use crate::compile::error::*;
+use crate::compile::schema::*;
+use crate::compile::sql::{compile_from, get_rowtype, select_from, with_table_alias};
+use crate::compile::traverse::{SQLVisitor, VisitSQL};
+use crate::compile::unsafe_expr::compile_unsafe_expr;
+
+// Qualifies the bare names of a model relation's columns in the model's expressions with the
+// model's name, which is the alias of its relation in the expanded query. This keeps them
+// unambiguous once other models are joined in. Other names (e.g. lets or externs) are left alone.
+//
+struct ModelQualifier {
+    model: sqlast::Located<sqlast::Ident>,
+    columns: BTreeSet<Ident>,
+}
+
+impl SQLVisitor for ModelQualifier {
+    fn visit_sqlexpr(&self, expr: &sqlast::Expr) -> Option<sqlast::Expr> {
+        let ident = match expr {
+            sqlast::Expr::Identifier(x) => x.clone(),
+            sqlast::Expr::CompoundIdentifier(v) if v.len() == 1 => v[0].clone(),
+            _ => return None,
+        };
+        if !self.columns.contains(&Ident::from(ident.get())) {
+            return None;
+        }
+        Some(sqlast::Expr::CompoundIdentifier(vec![
+            self.model.clone(),
+            ident,
+        ]))
+    }
+}
+
+fn qualify(model: &Reachable, expr: &sqlast::Expr) -> sqlast::Expr {
+    expr.visit_sql(&ModelQualifier {
+        model: model.model.name.to_sqlident(),
+        columns: model.columns.clone(),
+    })
+}
+
+// A model's relation defaults to the relation with the same name, and is aliased to the model's
+// name either way.
+//
+fn model_table(model: &ast::Model) -> sqlast::TableFactor {
+    let alias = sqlast::TableAlias {
+        name: model.name.to_sqlident(),
+        columns: Vec::new(),
+    };
+    match &model.relation {
+        Some(relation) => with_table_alias(relation, Some(alias)),
+        None => sqlast::TableFactor::Table {
+            name: sqlast::ObjectName(vec![model.name.to_sqlident()]),
+            alias: Some(alias),
+            args: None,
+            with_hints: Vec::new(),
+        },
+    }
+}
+
+// The names of the columns of a model's relation.
+//
+async fn model_columns(
+    compiler: &Compiler,
+    schema: &SchemaRef,
+    loc: &SourceLocation,
+    model: &ast::Model,
+) -> Result<BTreeSet<Ident>> {
+    let (scope, _) = compile_from(
+        compiler,
+        schema,
+        None,
+        loc,
+        &vec![sqlast::TableWithJoins {
+            relation: model_table(model),
+            joins: Vec::new(),
+        }],
+    )?;
+    let relation = scope.read()?.get_relation(model.name.get())?;
+    let type_ = match relation {
+        Some((type_, _)) => type_,
+        None => {
+            return Err(CompileError::internal(
+                loc.clone(),
+                "model relation is missing from its scope",
+            ))
+        }
+    };
+
+    let rowtype = get_rowtype(compiler.clone(), type_)?.await?;
+    let columns = match &*rowtype.read()? {
+        MType::Record(fields) => fields.get().iter().map(|f| f.name.clone()).collect(),
+        _ => {
+            return Err(CompileError::model_query(
+                loc.clone(),
+                model.name.get(),
+                "expected the model's relation to have record rows",
+            ))
+        }
+    };
+    Ok(columns)
+}
+
+pub fn lookup_model<'a>(
+    schema: &'a Schema,
+    name: &ast::Located<ast::Ident>,
+) -> Result<&'a ModelDecl> {
+    schema
+        .models
+        .get(name.get())
+        .ok_or_else(|| CompileError::no_such_entry(vec![name.clone()]))
+}
+
+// Compiles a model query as the SQL query that it expands to. Imported models are expanded and
+// compiled in the schema that declares them, since that's where their names are bound.
+//
+pub fn compile_model_query(
+    compiler: Compiler,
+    schema: SchemaRef,
+    loc: &SourceLocation,
+    query: &ast::ModelQuery,
+    is_unsafe: bool,
+) -> Result<CTypedExpr> {
+    let (model_schema, name) = {
+        let schema_ref = schema.read()?;
+        let decl = lookup_model(&schema_ref, &query.model)?;
+        (
+            decl.schema.clone().unwrap_or_else(|| schema.clone()),
+            decl.model.name.get().clone(),
+        )
+    };
+    let query = ast::ModelQuery {
+        model: Located::new(name, query.model.location().clone()),
+        ..query.clone()
+    };
+
+    let type_ = MType::new_unknown(query.model.get().as_str());
+    let loc = loc.clone();
+    let expr = compiler.async_cref({
+        let compiler = compiler.clone();
+        let type_ = type_.clone();
+        async move {
+            let sql = expand_model_query(&compiler, &model_schema, &loc, &query).await?;
+            let compiled = if is_unsafe {
+                compile_unsafe_expr(compiler, model_schema, &ast::ExprBody::SQLQuery(sql), &loc)?
+            } else {
+                compile_query_expr(compiler, model_schema, &loc, &sql)?
+            };
+            type_.unify(&compiled.type_)?;
+            Ok(compiled.expr)
+        }
+    })?;
+
+    Ok(CTypedExpr { type_, expr })
+}
+
+// A model that can be reached from the queried model, along with the index (into the list of
+// reachable models) of the model that joins it in and the join itself.
+//
+struct Reachable {
+    model: ast::Model,
+    parent: Option<(usize, ast::ModelJoin)>,
+    columns: BTreeSet<Ident>,
+}
+
+// Expands a model query into the SQL query it stands for. The requested dimensions and measures
+// are selected from the model's relation, left joined with the models that any of the dimensions
+// come from, and the measures are grouped by the dimensions.
+//
+pub async fn expand_model_query(
+    compiler: &Compiler,
+    schema: &SchemaRef,
+    loc: &SourceLocation,
+    query: &ast::ModelQuery,
+) -> Result<sqlast::Query> {
+    let root = lookup_model(&*schema.read()?, &query.model)?.model.clone();
+    if query.measures.is_empty() && query.dimensions.is_empty() {
+        return Err(CompileError::model_query(
+            loc.clone(),
+            root.name.get(),
+            "expected at least one measure or dimension",
+        ));
+    }
+
+    // Models are visited breadth-first, so that each one is joined in after the model that it's
+    // joined to. Each model can only be reached once, or else its fields would be ambiguous.
+    //
+    let mut reachable = vec![Reachable {
+        model: root.clone(),
+        parent: None,
+        columns: BTreeSet::new(),
+    }];
+    let mut i = 0;
+    while i < reachable.len() {
+        for join in reachable[i].model.joins.clone() {
+            if reachable
+                .iter()
+                .any(|r| r.model.name.get() == join.model.get())
+            {
+                return Err(CompileError::model_query(
+                    join.model.location().clone(),
+                    root.name.get(),
+                    format!("{} is joined more than once", join.model).as_str(),
+                ));
+            }
+
+            let (model, imported) = {
+                let schema_ref = schema.read()?;
+                let decl = lookup_model(&schema_ref, &join.model)?;
+                (decl.model.clone(), decl.schema.is_some())
+            };
+            if imported {
+                return Err(CompileError::unimplemented(
+                    join.model.location().clone(),
+                    "joining imported models",
+                ));
+            }
+            reachable.push(Reachable {
+                model,
+                parent: Some((i, join)),
+                columns: BTreeSet::new(),
+            });
+        }
+        i += 1;
+    }
+
+    for r in reachable.iter_mut() {
+        r.columns = model_columns(compiler, schema, loc, &r.model).await?;
+    }
+
+    let mut projection = Vec::new();
+    let mut aliases = BTreeSet::new();
+    let mut needed = BTreeSet::from([0]);
+    for path in query.dimensions.iter() {
+        let (idx, field) = resolve_field(&reachable, path, false /* measure */)?;
+        projection.push(project_field(
+            &reachable,
+            &root,
+            &mut aliases,
+            path,
+            idx,
+            field,
+        )?);
+
+        let mut idx = Some(idx);
+        while let Some(i) = idx {
+            needed.insert(i);
+            idx = reachable[i].parent.as_ref().map(|(parent, _)| *parent);
+        }
+    }
+
+    for path in query.measures.iter() {
+        let (idx, field) = resolve_field(&reachable, path, true /* measure */)?;
+
+        // A join can match the same row of the joined model more than once, so aggregating its
+        // measures over the joined rows would over count them.
+        //
+        if idx != 0 {
+            return Err(CompileError::model_query(
+                path_location(path),
+                root.name.get(),
+                format!(
+                    "measures can only be taken from the queried model (query {} instead)",
+                    reachable[idx].model.name
+                )
+                .as_str(),
+            ));
+        }
+        projection.push(project_field(
+            &reachable,
+            &root,
+            &mut aliases,
+            path,
+            idx,
+            field,
+        )?);
+    }
+
+    let joins = needed
+        .iter()
+        .filter_map(|i| {
+            reachable[*i]
+                .parent
+                .as_ref()
+                .map(|(parent, join)| (*i, *parent, join))
+        })
+        .map(|(i, parent, join)| sqlast::Join {
+            relation: model_table(&reachable[i].model),
+            join_operator: sqlast::JoinOperator::LeftOuter(sqlast::JoinConstraint::On(qualify(
+                &reachable[parent],
+                &join.on,
+            ))),
+        })
+        .collect();
+
+    let mut sql = select_from(
+        projection,
+        vec![sqlast::TableWithJoins {
+            relation: model_table(&root),
+            joins,
+        }],
+    );
+    if let sqlast::SetExpr::Select(select) = sql.body.as_mut() {
+        select.group_by = (1..=query.dimensions.len())
+            .map(|i| sqlast::Expr::Value(sqlast::Value::Number(i.to_string(), false)))
+            .collect();
+    }
+
+    Ok(sql)
+}
+
+// Resolves a measure or dimension in a model query, which is either a field of the queried model
+// (`revenue`) or of a joined model (`users.city`).
+//
+fn resolve_field<'a>(
+    reachable: &'a [Reachable],
+    path: &ast::Path,
+    measure: bool,
+) -> Result<(usize, &'a ast::ModelField)> {
+    let (idx, name) = match path.as_slice() {
+        [name] => (0, name),
+        [model, name] => match reachable
+            .iter()
+            .position(|r| r.model.name.get() == model.get())
+        {
+            Some(idx) => (idx, name),
+            None => return Err(CompileError::no_such_entry(path.clone())),
+        },
+        _ => return Err(CompileError::no_such_entry(path.clone())),
+    };
+
+    let model = &reachable[idx].model;
+    let fields = if measure {
+        &model.measures
+    } else {
+        &model.dimensions
+    };
+    match fields.iter().find(|f| f.name.get() == name.get()) {
+        Some(field) => Ok((idx, field)),
+        None => Err(CompileError::no_such_entry(path.clone())),
+    }
+}
+
+// Fields of joined models are named after the model as well, e.g. `users_city`, so that they don't
+// collide with the queried model's fields. A name can still collide (e.g. with a queried model
+// field that is itself named `users_city`), in which case the query is rejected.
+//
+fn project_field(
+    reachable: &[Reachable],
+    root: &ast::Model,
+    aliases: &mut BTreeSet<Ident>,
+    path: &ast::Path,
+    idx: usize,
+    field: &ast::ModelField,
+) -> Result<sqlast::SelectItem> {
+    let model = &reachable[idx].model;
+    let alias = if idx == 0 {
+        field.name.to_sqlident()
+    } else {
+        sqlast::Ident::new(format!("{}_{}", model.name.get(), field.name.get()))
+    };
+
+    if !aliases.insert(Ident::from(alias.get())) {
+        return Err(CompileError::model_query(
+            path_location(path),
+            root.name.get(),
+            format!("{} is selected more than once", alias.get().value).as_str(),
+        ));
+    }
+
+    Ok(sqlast::SelectItem::ExprWithAlias {
+        expr: qualify(&reachable[idx], &field.expr),
+        alias,
+    })
+}
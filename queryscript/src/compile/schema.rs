@@ -29,12 +29,20 @@ pub struct MField {
     pub name: Ident,
     pub type_: CRef<MType>,
     pub nullable: bool,
+
+    // Where the field is declared, which is carried along when the field is included in (or
+    // projected into) other record types, so that references to it can point back to it.
+    pub loc: SourceLocation,
 }
 
 #[derive(Debug, Clone)]
 pub struct MFnType {
     pub args: Vec<MField>,
     pub ret: CRef<MType>,
+
+    // Whether this is the type of a function argument (e.g. `fn(bigint) -> bigint`), whose
+    // arguments are unnamed and so are matched by position.
+    pub anonymous: bool,
 }
 
 impl MField {
@@ -43,6 +51,7 @@ impl MField {
             name,
             type_,
             nullable: true,
+            loc: SourceLocation::Unknown,
         }
     }
 }
@@ -57,6 +66,7 @@ pub enum MType {
     Record(Located<Vec<MField>>),
     List(Located<CRef<MType>>),
     Fn(Located<MFnType>),
+    Union(Located<Vec<MField>>),
     Name(Located<Ident>),
     Generic(Located<Arc<dyn Generic>>),
 }
@@ -69,18 +79,8 @@ impl MType {
     pub fn to_runtime_type(&self) -> runtime::error::Result<Type> {
         match self {
             MType::Atom(a) => Ok(Type::Atom(a.get().clone())),
-            MType::Record(fields) => Ok(Type::Record(
-                fields
-                    .iter()
-                    .map(|f| {
-                        Ok(Field {
-                            name: f.name.clone(),
-                            type_: f.type_.must()?.read()?.to_runtime_type()?,
-                            nullable: f.nullable,
-                        })
-                    })
-                    .collect::<runtime::error::Result<Vec<_>>>()?,
-            )),
+            MType::Record(fields) => Ok(Type::Record(fields_to_runtime_type(fields)?)),
+            MType::Union(fields) => Ok(Type::Union(fields_to_runtime_type(fields)?)),
             MType::List(inner) => Ok(Type::List(Box::new(
                 inner.must()?.read()?.to_runtime_type()?,
             ))),
@@ -112,16 +112,11 @@ impl MType {
                 SourceLocation::Unknown,
             ))),
             Type::Record(fields) => Ok(MType::Record(Located::new(
-                fields
-                    .iter()
-                    .map(|f| {
-                        Ok(MField {
-                            name: f.name.clone(),
-                            type_: mkcref(MType::from_runtime_type(&f.type_)?),
-                            nullable: f.nullable,
-                        })
-                    })
-                    .collect::<Result<Vec<_>>>()?,
+                fields_from_runtime_type(fields)?,
+                SourceLocation::Unknown,
+            ))),
+            Type::Union(fields) => Ok(MType::Union(Located::new(
+                fields_from_runtime_type(fields)?,
                 SourceLocation::Unknown,
             ))),
             Type::List(inner) => Ok(MType::List(Located::new(
@@ -137,10 +132,12 @@ impl MType {
                                 name: a.name.clone(),
                                 type_: mkcref(MType::from_runtime_type(&a.type_)?),
                                 nullable: a.nullable,
+                                loc: SourceLocation::Unknown,
                             })
                         })
                         .collect::<Result<Vec<_>>>()?,
                     ret: mkcref(MType::from_runtime_type(&ret)?),
+                    anonymous: false,
                 },
                 SourceLocation::Unknown,
             ))),
@@ -151,16 +148,11 @@ impl MType {
         let type_ = match self {
             MType::Atom(a) => mkcref(MType::Atom(a.clone())),
             MType::Record(fields) => mkcref(MType::Record(Located::new(
-                fields
-                    .iter()
-                    .map(|f| {
-                        Ok(MField {
-                            name: f.name.clone(),
-                            type_: f.type_.substitute(variables)?,
-                            nullable: f.nullable,
-                        })
-                    })
-                    .collect::<Result<_>>()?,
+                substitute_fields(fields, variables)?,
+                fields.location().clone(),
+            ))),
+            MType::Union(fields) => mkcref(MType::Union(Located::new(
+                substitute_fields(fields, variables)?,
                 fields.location().clone(),
             ))),
             MType::List(inner) => mkcref(MType::List(Located::new(
@@ -168,7 +160,11 @@ impl MType {
                 inner.location().clone(),
             ))),
             MType::Fn(mfn) => {
-                let MFnType { args, ret } = mfn.get();
+                let MFnType {
+                    args,
+                    ret,
+                    anonymous,
+                } = mfn.get();
                 let location = mfn.location();
                 mkcref(MType::Fn(Located::new(
                     MFnType {
@@ -179,10 +175,12 @@ impl MType {
                                     name: a.name.clone(),
                                     type_: a.type_.substitute(variables)?,
                                     nullable: a.nullable,
+                                    loc: a.loc.clone(),
                                 })
                             })
                             .collect::<Result<_>>()?,
                         ret: ret.substitute(variables)?,
+                        anonymous: *anonymous,
                     },
                     location.clone(),
                 )))
@@ -209,12 +207,57 @@ impl MType {
             MType::Record(t) => t.location().clone(),
             MType::List(t) => t.location().clone(),
             MType::Fn(t) => t.location().clone(),
+            MType::Union(t) => t.location().clone(),
             MType::Name(t) => t.location().clone(),
             MType::Generic(t) => t.location().clone(),
         }
     }
 }
 
+fn fields_to_runtime_type(fields: &Vec<MField>) -> runtime::error::Result<Vec<Field>> {
+    fields
+        .iter()
+        .map(|f| {
+            Ok(Field {
+                name: f.name.clone(),
+                type_: f.type_.must()?.read()?.to_runtime_type()?,
+                nullable: f.nullable,
+            })
+        })
+        .collect()
+}
+
+fn fields_from_runtime_type(fields: &Vec<Field>) -> Result<Vec<MField>> {
+    fields
+        .iter()
+        .map(|f| {
+            Ok(MField {
+                name: f.name.clone(),
+                type_: mkcref(MType::from_runtime_type(&f.type_)?),
+                nullable: f.nullable,
+                loc: SourceLocation::Unknown,
+            })
+        })
+        .collect()
+}
+
+fn substitute_fields(
+    fields: &Vec<MField>,
+    variables: &BTreeMap<Ident, CRef<MType>>,
+) -> Result<Vec<MField>> {
+    fields
+        .iter()
+        .map(|f| {
+            Ok(MField {
+                name: f.name.clone(),
+                type_: f.type_.substitute(variables)?,
+                nullable: f.nullable,
+                loc: f.loc.clone(),
+            })
+        })
+        .collect()
+}
+
 impl Pretty for MType {
     fn pretty(&self) -> String {
         format!("{:?}", self).white().bold().to_string()
@@ -225,6 +268,7 @@ impl Into<SType> for CRef<MType> {
     fn into(self) -> SType {
         SType {
             variables: BTreeSet::new(),
+            bounds: BTreeMap::new(),
             body: self,
         }
     }
@@ -312,12 +356,16 @@ impl fmt::Debug for MType {
                 f.write_str("]")?;
             }
             MType::Fn(mfn) => {
-                let MFnType { args, ret } = mfn.get();
+                let MFnType { args, ret, .. } = mfn.get();
                 f.write_str("λ ")?;
                 DebugMFields(&args).fmt(f)?;
                 f.write_str(" -> ")?;
                 ret.fmt(f)?;
             }
+            MType::Union(fields) => {
+                f.write_str("union ")?;
+                DebugMFields(fields).fmt(f)?;
+            }
             MType::Name(n) => n.get().fmt(f)?,
             MType::Generic(t) => {
                 t.get().fmt(f)?;
@@ -355,6 +403,7 @@ impl Constrainable for MType {
                 let MFnType {
                     args: largs,
                     ret: lret,
+                    anonymous: lanonymous,
                 } = lmfn.get();
                 let lloc = lmfn.location();
                 match other {
@@ -362,15 +411,42 @@ impl Constrainable for MType {
                         let MFnType {
                             args: rargs,
                             ret: rret,
+                            anonymous: ranonymous,
                         } = rmfn.get();
                         let rloc = rmfn.location();
-                        Located::new(largs.clone(), lloc.clone())
-                            .unify(&Located::new(rargs.clone(), rloc.clone()))?;
+                        if *lanonymous || *ranonymous {
+                            // Function types (e.g. `fn(bigint) -> bigint`) don't name their
+                            // arguments, so they're matched by position.
+                            if largs.len() != rargs.len() {
+                                return Err(CompileError::wrong_type(self, other));
+                            }
+                            for (larg, rarg) in largs.iter().zip(rargs.iter()) {
+                                larg.type_.unify(&rarg.type_)?;
+                            }
+                        } else {
+                            Located::new(largs.clone(), lloc.clone())
+                                .unify(&Located::new(rargs.clone(), rloc.clone()))?;
+                        }
                         lret.unify(rret)?;
                     }
                     _ => return Err(CompileError::wrong_type(self, other)),
                 }
             }
+            MType::Union(lfields) => match other {
+                // Unions are tagged, so their members must match by name and in order.
+                MType::Union(rfields)
+                    if lfields.len() == rfields.len()
+                        && lfields
+                            .iter()
+                            .zip(rfields.iter())
+                            .all(|(l, r)| l.name == r.name) =>
+                {
+                    for (l, r) in lfields.iter().zip(rfields.iter()) {
+                        l.type_.unify(&r.type_)?;
+                    }
+                }
+                _ => return Err(CompileError::wrong_type(self, other)),
+            },
             MType::Name(name) => {
                 return Err(CompileError::internal(
                     name.location().clone(),
@@ -428,10 +504,9 @@ impl Constrainable for Located<Vec<MField>> {
                 return Err(err());
             }
 
-            if self[i].nullable != other[i].nullable {
-                return Err(err());
-            }
-
+            // Nullability isn't unified, since unification doesn't know which side is expected.
+            // It's checked separately with `assignable_to`.
+            //
             self[i].type_.unify(&other[i].type_)?;
         }
 
@@ -439,7 +514,47 @@ impl Constrainable for Located<Vec<MField>> {
     }
 }
 
+impl MType {
+    // Checks that a value of this type can be used where `expected` is expected, once the two have
+    // been unified. A non-null field can be used where a nullable one is expected, but not the
+    // other way around. Types that aren't known yet are assumed to be assignable.
+    //
+    pub fn assignable_to(&self, expected: &MType) -> Result<()> {
+        match (self, expected) {
+            (MType::Record(fields), MType::Record(expected_fields)) => {
+                for (field, expected_field) in fields.iter().zip(expected_fields.iter()) {
+                    if field.nullable && !expected_field.nullable {
+                        return Err(CompileError::wrong_type(expected, self));
+                    }
+                    field.type_.assignable_to(&expected_field.type_)?;
+                }
+            }
+            (MType::List(inner), MType::List(expected_inner)) => {
+                inner.get().assignable_to(expected_inner.get())?
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
 impl CRef<MType> {
+    pub fn assignable_to(&self, expected: &CRef<MType>) -> Result<()> {
+        let type_ = match &*self.read()? {
+            Constrained::Known(t) => t.clone(),
+            Constrained::Unknown { .. } => return Ok(()),
+            Constrained::Ref(r) => return r.assignable_to(expected),
+        };
+        let expected = match &*expected.read()? {
+            Constrained::Known(e) => e.clone(),
+            Constrained::Unknown { .. } => return Ok(()),
+            Constrained::Ref(r) => return self.assignable_to(r),
+        };
+        let type_ = type_.read()?;
+        let expected = expected.read()?;
+        type_.assignable_to(&expected)
+    }
+
     pub fn substitute(&self, variables: &BTreeMap<Ident, CRef<MType>>) -> Result<CRef<MType>> {
         match &*self.read()? {
             Constrained::Known(t) => t.read()?.substitute(variables),
@@ -469,19 +584,39 @@ pub type Ref<T> = Arc<RwLock<T>>;
 #[derive(Clone)]
 pub struct SType {
     pub variables: BTreeSet<Ident>,
+    // The record types that (some of) the variables must conform to when instantiated
+    pub bounds: BTreeMap<Ident, GenericBound>,
     pub body: CRef<MType>,
 }
 
+// The bound of a generic argument, e.g. `{id bigint, ..}`. Types with other fields than the
+// bound's only satisfy it if it's open (i.e. ends with `..`).
+//
+#[derive(Clone, Debug)]
+pub struct GenericBound {
+    pub type_: CRef<MType>,
+    pub open: bool,
+}
+
 impl SType {
     pub fn new_mono(body: CRef<MType>) -> CRef<SType> {
         mkcref(SType {
             variables: BTreeSet::new(),
+            bounds: BTreeMap::new(),
             body,
         })
     }
 
-    pub fn new_poly(body: CRef<MType>, variables: BTreeSet<Ident>) -> CRef<SType> {
-        mkcref(SType { variables, body })
+    pub fn new_poly(
+        body: CRef<MType>,
+        variables: BTreeSet<Ident>,
+        bounds: BTreeMap<Ident, GenericBound>,
+    ) -> CRef<SType> {
+        mkcref(SType {
+            variables,
+            bounds,
+            body,
+        })
     }
 }
 
@@ -501,6 +636,12 @@ impl fmt::Debug for SType {
     }
 }
 
+impl Pretty for SType {
+    fn pretty(&self) -> String {
+        format!("{:?}", self).white().bold().to_string()
+    }
+}
+
 impl Constrainable for SType {}
 
 #[derive(Clone)]
@@ -756,6 +897,8 @@ pub enum FnKind {
     SQLBuiltin,
     Native,
     Expr,
+    // A function passed as an argument, which is only known once the enclosing function is called
+    Argument,
 }
 
 #[derive(Debug, Clone)]
@@ -783,6 +926,12 @@ where
 {
     pub inner_schema: Ref<Schema>,
     pub body: FnBody<TypeRef>,
+
+    // The names of the function's arguments, in order.
+    pub args: Vec<Ident>,
+
+    // Aggregate functions (declared with `agg fn`) may only be called where SQL allows aggregates.
+    pub aggregate: bool,
 }
 
 impl<TypeRef: Clone + fmt::Debug + Send + Sync> fmt::Debug for FnExpr<TypeRef> {
@@ -834,9 +983,16 @@ impl Expr<CRef<MType>> {
                     body: body.clone(),
                 })))
             }
-            Expr::Fn(FnExpr { inner_schema, body }) => Ok(Expr::Fn(FnExpr {
+            Expr::Fn(FnExpr {
+                inner_schema,
+                body,
+                args,
+                aggregate,
+            }) => Ok(Expr::Fn(FnExpr {
                 inner_schema: inner_schema.clone(),
                 body: body.to_runtime_type()?,
+                args: args.clone(),
+                aggregate: *aggregate,
             })),
             Expr::FnCall(FnCallExpr {
                 func,
@@ -989,6 +1145,21 @@ impl Entry for ExprEntry {
     }
 }
 
+// The documentation written in `---` comments before a declaration and, for record types, before
+// each of its fields.
+//
+#[derive(Clone, Debug, Default)]
+pub struct Doc {
+    pub text: Option<String>,
+    pub fields: BTreeMap<Ident, String>,
+}
+
+impl Doc {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.fields.is_empty()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Decl<Entry: Clone> {
     pub public: bool,
@@ -996,6 +1167,7 @@ pub struct Decl<Entry: Clone> {
     pub fn_arg: bool,
     pub name: Located<Ident>,
     pub value: Entry,
+    pub doc: Doc,
 }
 
 #[derive(Clone, Debug)]
@@ -1024,6 +1196,20 @@ impl Constrainable for TypedNameAndExpr<CRef<MType>> {}
 
 pub type SchemaRef = Ref<Schema>;
 
+#[derive(Clone, Debug)]
+pub enum TestKind {
+    // `test "name" { <query> } expect empty`, which passes if the query returns no rows.
+    ExpectEmpty { name: String },
+    // `assert <expr>`, which passes if the expression is true.
+    Assert,
+}
+
+#[derive(Clone, Debug)]
+pub struct SchemaTest {
+    pub kind: TestKind,
+    pub expr: CTypedExpr,
+}
+
 #[derive(Clone, Debug)]
 pub struct TypedName<TypeRef> {
     pub name: Ident,
@@ -1032,7 +1218,9 @@ pub struct TypedName<TypeRef> {
 
 #[derive(Clone, Debug)]
 pub struct ImportedSchema {
-    pub args: Option<Vec<BTreeMap<String, TypedNameAndExpr<CRef<MType>>>>>,
+    // Schemas with extern declarations are instantiated once per parameterized import. This
+    // is None for schemas without externs, which are imported directly.
+    pub instances: Option<Vec<SchemaRef>>,
     pub schema: SchemaRef,
 }
 
@@ -1050,6 +1238,19 @@ pub struct Schema {
     pub folder: Option<String>,
     pub parent_scope: Option<Ref<Schema>>,
     pub externs: BTreeMap<Ident, CRef<MType>>,
+    pub extern_args: BTreeMap<Ident, CTypedNameAndExpr>,
+    pub positional_params: usize,
+
+    // The default values of a function's arguments, which are compiled in the scope of the
+    // function's declaration (not its body).
+    //
+    pub arg_defaults: BTreeMap<Ident, CTypedExpr>,
+
+    // The overloads of each function, i.e. the functions declared (or imported) with the same
+    // name after the first one, which is declared in expr_decls like any other function. Every
+    // function declared in this schema has an entry, even if it is not overloaded.
+    //
+    pub overloads: BTreeMap<Ident, Vec<Located<Decl<STypedExpr>>>>,
 
     pub schema_decls: DeclMap<ast::Path>,
     pub type_decls: DeclMap<CRef<MType>>,
@@ -1057,6 +1258,25 @@ pub struct Schema {
 
     pub imports: BTreeMap<Vec<Ident>, Ref<ImportedSchema>>,
     pub exprs: Vec<Located<CTypedExpr>>,
+
+    // Data tests declared in this schema, which are not evaluated with `exprs` and only run
+    // under `qs test`.
+    //
+    pub tests: Vec<Located<SchemaTest>>,
+
+    // Models are only used to expand model queries, so they're kept as they were parsed.
+    //
+    pub models: BTreeMap<Ident, ModelDecl>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModelDecl {
+    pub public: bool,
+    pub model: ast::Model,
+
+    // The schema that an imported model was declared in, which its relation, joins, and
+    // expressions are resolved in. This is None for models declared in this schema.
+    pub schema: Option<SchemaRef>,
 }
 
 impl Schema {
@@ -1066,11 +1286,17 @@ impl Schema {
             folder,
             parent_scope: None,
             externs: BTreeMap::new(),
+            extern_args: BTreeMap::new(),
+            positional_params: 0,
+            arg_defaults: BTreeMap::new(),
+            overloads: BTreeMap::new(),
             schema_decls: BTreeMap::new(),
             type_decls: BTreeMap::new(),
             expr_decls: BTreeMap::new(),
             imports: BTreeMap::new(),
             exprs: Vec::new(),
+            tests: Vec::new(),
+            models: BTreeMap::new(),
         })
     }
 
@@ -1087,11 +1313,7 @@ impl Schema {
         match self.get_decls::<E>().get(ident) {
             Some(decl) => {
                 if check_visibility && !decl.public {
-                    return Err(CompileError::wrong_kind(
-                        full_path.clone(),
-                        "public",
-                        E::kind(),
-                    ));
+                    return Err(CompileError::private_decl(full_path.clone()));
                 } else {
                     Ok(Some(decl))
                 }
@@ -1102,3 +1324,51 @@ impl Schema {
 }
 
 pub const SCHEMA_EXTENSIONS: &[&str] = &["qs"];
+
+// Directories (separated like the system PATH) that are searched for imported schemas
+pub const SEARCH_PATH_ENV: &str = "QS_PATH";
+
+// A project file lists additional search directories, one per line, relative to the project root
+pub const PROJECT_FILE: &str = ".qspath";
+
+// Vendored packages live in this directory under the project root
+pub const PACKAGES_DIR: &str = "qs_packages";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(nullable: bool) -> MType {
+        MType::Record(Located::new(
+            vec![MField {
+                nullable,
+                ..MField::new_nullable(
+                    "name".into(),
+                    mkcref(MType::Atom(Located::new(
+                        AtomicType::Utf8,
+                        SourceLocation::Unknown,
+                    ))),
+                )
+            }],
+            SourceLocation::Unknown,
+        ))
+    }
+
+    // Unification is symmetric, so a non-null and a nullable record unify in either order, while
+    // `assignable_to` only allows a non-null field where a nullable one is expected.
+    //
+    #[test]
+    fn test_nullability_is_directional() {
+        let (not_null, nullable) = (record(false), record(true));
+        not_null.unify(&nullable).unwrap();
+        nullable.unify(&not_null).unwrap();
+
+        not_null.assignable_to(&nullable).unwrap();
+        assert!(nullable.assignable_to(&not_null).is_err());
+
+        let list =
+            |t: &MType| MType::List(Located::new(mkcref(t.clone()), SourceLocation::Unknown));
+        list(&not_null).assignable_to(&list(&nullable)).unwrap();
+        assert!(list(&nullable).assignable_to(&list(&not_null)).is_err());
+    }
+}
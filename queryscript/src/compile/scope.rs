@@ -1,4 +1,4 @@
-use std::collections::{btree_map, BTreeMap};
+use std::collections::{btree_map, BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use crate::ast::SourceLocation;
@@ -15,6 +15,7 @@ pub struct FieldMatch {
     pub relation: Located<Ident>,
     pub field: Located<Ident>,
     pub type_: Option<CRef<MType>>,
+    pub nullable: bool,
 }
 impl Constrainable for FieldMatch {}
 
@@ -22,6 +23,14 @@ impl Constrainable for FieldMatch {}
 pub struct SQLScope {
     parent: Option<Ref<SQLScope>>,
     relations: BTreeMap<Ident, (CRef<MType>, SourceLocation)>,
+
+    // Relations on the outer side of an outer join, whose fields may be NULL even if they're
+    // declared as non-null.
+    nullable_relations: BTreeSet<Ident>,
+
+    // Whether aggregate functions may be called in expressions compiled against this scope (i.e.
+    // in the SELECT list or ORDER BY of a query, but not in its WHERE or GROUP BY clauses).
+    allow_aggregates: bool,
 }
 
 impl SQLScope {
@@ -29,13 +38,31 @@ impl SQLScope {
         mkref(SQLScope {
             parent,
             relations: BTreeMap::new(),
+            nullable_relations: BTreeSet::new(),
+            allow_aggregates: false,
         })
     }
 
+    // Returns a copy of this scope (with the same relations) in which aggregates may be called.
+    pub fn with_aggregates(&self) -> Ref<SQLScope> {
+        mkref(SQLScope {
+            allow_aggregates: true,
+            ..self.clone()
+        })
+    }
+
+    pub fn allow_aggregates(&self) -> bool {
+        self.allow_aggregates
+    }
+
     pub fn empty() -> Ref<SQLScope> {
         Self::new(None)
     }
 
+    pub fn parent(&self) -> Option<Ref<SQLScope>> {
+        self.parent.clone()
+    }
+
     pub fn get_relation(&self, name: &Ident) -> Result<Option<(CRef<MType>, SourceLocation)>> {
         Ok(match self.relations.get(name) {
             Some((t, loc)) => Some((t.clone(), loc.clone())),
@@ -60,6 +87,7 @@ impl SQLScope {
                     None => true,
                 })
                 .map(|(n, (te, loc))| {
+                    let relation_nullable = self.nullable_relations.contains(n);
                     let n = Ident::with_location(loc.clone(), n.clone());
                     get_rowtype(compiler.clone(), te.clone())?.then(move |rowtype: Ref<MType>| {
                         let rowtype = rowtype.read()?.clone();
@@ -69,8 +97,12 @@ impl SQLScope {
                                     .iter()
                                     .map(|field| FieldMatch {
                                         relation: n.clone(),
-                                        field: Ident::without_location(field.name.clone()),
+                                        field: Ident::with_location(
+                                            field.loc.clone(),
+                                            field.name.clone(),
+                                        ),
                                         type_: Some(field.type_.clone()),
+                                        nullable: relation_nullable || field.nullable,
                                     })
                                     .collect(),
                             )),
@@ -78,6 +110,7 @@ impl SQLScope {
                                 relation: n.clone(),
                                 field: n.clone(),
                                 type_: Some(mkcref(rowtype)),
+                                nullable: true,
                             }])),
                         }
                     })
@@ -170,6 +203,14 @@ impl SQLScope {
         };
         Ok(())
     }
+
+    pub fn relation_names(&self) -> BTreeSet<Ident> {
+        self.relations.keys().cloned().collect()
+    }
+
+    pub fn set_nullable(&mut self, name: &Ident) {
+        self.nullable_relations.insert(name.clone());
+    }
 }
 
 impl Constrainable for SQLScope {}
@@ -7,9 +7,9 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::sync::Arc;
 
-use crate::compile::coerce::CoerceOp;
+use crate::compile::coerce::{unary_coerce_type, CoerceOp};
 use crate::compile::compile::{
-    coerce, lookup_path, resolve_global_atom, typecheck_path, Compiler, SymbolKind,
+    coerce, extern_value, lookup_path, resolve_global_atom, typecheck_path, Compiler, SymbolKind,
 };
 use crate::compile::error::*;
 use crate::compile::generics::{as_generic, ExternalType};
@@ -20,12 +20,12 @@ use crate::compile::scope::{AvailableReferences, SQLScope};
 use crate::types::{number::parse_numeric_type, AtomicType, Type};
 use crate::{
     ast,
-    ast::{SourceLocation, ToPath, ToSqlIdent},
+    ast::{Location, Pretty, Range, SourceLocation, ToPath, ToSqlIdent},
 };
 
 use super::compile::ExternalTypeRank;
 
-const QS_NAMESPACE: &str = "__qs";
+pub const QS_NAMESPACE: &str = "__qs";
 
 #[derive(Clone, Debug)]
 pub struct TypedSQL {
@@ -160,6 +160,16 @@ pub fn with_table_alias(
     table
 }
 
+// Fields that were declared somewhere (e.g. in a type definition or a SELECT list) are defined
+// there, while others are defined by the relation they come from.
+//
+fn field_definition(field: &Located<Ident>, relation: &Located<Ident>) -> SourceLocation {
+    match field.location() {
+        SourceLocation::Unknown => relation.location().clone(),
+        loc => loc.clone(),
+    }
+}
+
 pub fn compile_sqlreference(
     compiler: Compiler,
     schema: Ref<Schema>,
@@ -210,7 +220,7 @@ pub fn compile_sqlreference(
                                     path[0].clone(),
                                     SymbolKind::Field,
                                     mkcref(type_.clone().into()),
-                                    fm.relation.location().clone(),
+                                    field_definition(&fm.field, &fm.relation),
                                     None,
                                 )?;
                                 Ok(mkcref(TypedExpr {
@@ -255,18 +265,42 @@ pub fn compile_sqlreference(
                 scope.read()?.get_relation(&relation_name)?
             {
                 let rowtype = get_rowtype(compiler.clone(), relation_type)?;
-                let type_ = typecheck_path(rowtype, vec![path[1].clone()].as_slice())?;
+                let type_ = typecheck_path(rowtype.clone(), vec![path[1].clone()].as_slice())?;
                 let expr = mkcref(Expr::SQL(Arc::new(SQL {
                     names: CSQLNames::from_unbound(&sqlpath),
                     body: SQLBody::Expr(sqlast::Expr::CompoundIdentifier(sqlpath.clone())),
                 })));
-                compiler.run_on_symbol::<ExprEntry>(
-                    path[1].clone(),
-                    SymbolKind::Value,
-                    mkcref(type_.clone().into()),
-                    relation_loc,
-                    None,
-                )?;
+
+                // The field's definition is only known once the relation's type is, so the
+                // symbol is recorded then.
+                //
+                let field_name = path[1].clone();
+                let field_type = type_.clone();
+                compiler.async_cref({
+                    let compiler = compiler.clone();
+                    async move {
+                        let relation = Ident::with_location(relation_loc, relation_name.clone());
+                        let field = match &*rowtype.await?.read()? {
+                            MType::Record(fields) => fields
+                                .iter()
+                                .find(|f| &f.name == field_name.get())
+                                .map(|f| Ident::with_location(f.loc.clone(), f.name.clone())),
+                            _ => None,
+                        };
+                        let def = match &field {
+                            Some(field) => field_definition(field, &relation),
+                            None => relation.location().clone(),
+                        };
+                        compiler.run_on_symbol::<ExprEntry>(
+                            field_name,
+                            SymbolKind::Value,
+                            mkcref(field_type.into()),
+                            def,
+                            None,
+                        )?;
+                        Ok(mkcref(()))
+                    }
+                })?;
                 return Ok(CTypedExpr { type_, expr });
             }
         }
@@ -297,12 +331,25 @@ pub fn compile_reference(
     )?;
 
     let decl = decl.ok_or_else(|| CompileError::no_such_entry(path.clone()))?;
+    compile_decl_reference(compiler, path, &decl, remainder)
+}
+
+// Compiles a reference (through `path`) to the declaration `decl`, followed by an access to the
+// fields in `remainder`.
+//
+fn compile_decl_reference(
+    compiler: Compiler,
+    path: &ast::Path,
+    decl: &Decl<ExprEntry>,
+    remainder: ast::Path,
+) -> Result<TypedExpr<CRef<MType>>> {
     let remainder_cpy = remainder.clone();
 
     let expr = &decl.value;
+    let loc = path_location(path);
     let type_ = expr
         .type_
-        .then(|t: Ref<SType>| Ok(t.read()?.instantiate()?))?;
+        .then(move |t: Ref<SType>| Ok(t.read()?.instantiate(&loc)?))?;
     typecheck_path(type_.clone(), remainder_cpy.as_slice())?;
 
     let top_level_ref = TypedExpr {
@@ -347,6 +394,83 @@ pub fn compile_reference(
     Ok(r)
 }
 
+// Placeholders ($1, ?, :name) are compiled into externs of the top-level schema, whose types are
+// inferred from how they're used (or come from an explicit `extern` declaration of the same name).
+// Anonymous placeholders (?) are numbered by their position, so the first one is named `?1` (which
+// keeps it distinct from `$1`).
+//
+fn compile_placeholder(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    loc: &SourceLocation,
+    placeholder: &str,
+) -> Result<CTypedExpr> {
+    let mut root = schema.clone();
+    loop {
+        let parent = root.read()?.parent_scope.clone();
+        match parent {
+            Some(parent) => root = parent,
+            None => break,
+        }
+    }
+
+    let name = match placeholder.trim_start_matches(|c| matches!(c, '$' | ':' | '@' | '?')) {
+        "" => {
+            let mut root = root.write()?;
+            root.positional_params += 1;
+            format!("?{}", root.positional_params)
+        }
+        name => name.to_string(),
+    };
+    let name = Ident::with_location(loc.clone(), name);
+
+    let existing = root
+        .read()?
+        .expr_decls
+        .get(name.get())
+        .map(|decl| decl.get().clone());
+    let decl = match existing {
+        Some(decl) if decl.extern_ => decl,
+        Some(_) => return Err(CompileError::wrong_kind(vec![name], "extern", "value")),
+        None => {
+            let type_ = MType::new_unknown(name.as_str());
+            let decl = Decl {
+                public: false,
+                extern_: true,
+                fn_arg: false,
+                name: name.clone(),
+                value: STypedExpr {
+                    type_: SType::new_mono(type_.clone()),
+                    expr: extern_value(&root, &name, &type_)?,
+                },
+                doc: Doc::default(),
+            };
+            root.write()?
+                .expr_decls
+                .insert(name.get().clone(), Located::new(decl.clone(), loc.clone()));
+            decl
+        }
+    };
+
+    let loc = name.location().clone();
+    let type_ = decl
+        .value
+        .type_
+        .then(move |t: Ref<SType>| Ok(t.read()?.instantiate(&loc)?))?;
+    compiler.run_on_symbol::<ExprEntry>(
+        name.clone(),
+        SymbolKind::Value,
+        decl.value.type_.clone(),
+        decl.name.location().clone(),
+        Some(decl.clone()),
+    )?;
+
+    Ok(CTypedExpr {
+        type_,
+        expr: mkcref(Expr::SchemaEntry(decl.value.clone())),
+    })
+}
+
 pub fn intern_placeholder(
     compiler: Compiler,
     kind: &str,
@@ -578,17 +702,22 @@ impl CompileSQL for sqlast::TableFactor {
             } => {
                 let loc = path_location(&name.0.to_path(file.clone()));
 
-                if args.is_some() {
-                    return Err(CompileError::unimplemented(
-                        loc.clone(),
-                        "Table valued functions",
-                    ));
-                }
-
                 if with_hints.len() > 0 {
                     return Err(CompileError::unimplemented(loc.clone(), "WITH hints"));
                 }
 
+                if let Some(args) = args {
+                    return compile_table_function(
+                        compiler,
+                        schema,
+                        scope,
+                        &loc,
+                        name,
+                        args,
+                        alias.as_ref(),
+                    );
+                }
+
                 // TODO: This currently assumes that table references always come from outside
                 // the query, which is not actually the case.
                 //
@@ -648,15 +777,9 @@ impl CompileSQL for sqlast::TableFactor {
                 subquery,
                 alias,
             } => {
-                if *lateral {
-                    // This is a lateral subquery, which I haven't tested yet (because it will require
-                    // forwarding the outer scope into the subquery).
-                    return Err(CompileError::unimplemented(
-                        loc.clone(),
-                        "Lateral Subqueries",
-                    ));
-                }
-
+                // Subqueries are compiled against the scope we're building, so that a lateral
+                // subquery can reference the relations that precede it in the FROM clause (the
+                // engine rejects such references from other subqueries).
                 // NOTE: Once we thread locations through the parse tree, we should use the location here.
                 let (_scope, subquery_type, subquery) = compile_sqlquery(
                     compiler.clone(),
@@ -705,8 +828,110 @@ impl CompileSQL for sqlast::TableFactor {
             sqlast::TableFactor::TableFunction { .. } => {
                 return Err(CompileError::unimplemented(loc.clone(), "TABLE"))
             }
-            sqlast::TableFactor::UNNEST { .. } => {
-                return Err(CompileError::unimplemented(loc.clone(), "UNNEST"))
+            sqlast::TableFactor::UNNEST {
+                alias,
+                array_expr,
+                with_offset,
+                with_offset_alias: _,
+            } => {
+                if *with_offset {
+                    return Err(CompileError::unimplemented(
+                        loc.clone(),
+                        "UNNEST ... WITH OFFSET",
+                    ));
+                }
+
+                // The array expression is allowed to reference relations that precede it in the FROM
+                // clause (e.g. FROM t, UNNEST(t.tags)), so it's compiled against the current scope.
+                let array = compile_sqlarg(
+                    compiler.clone(),
+                    schema.clone(),
+                    scope.clone(),
+                    loc,
+                    array_expr,
+                )?;
+
+                // Unnesting a list produces a relation whose rows are the list's elements, which is
+                // exactly the list type itself.
+                let element_type = MType::new_unknown("UNNEST element");
+                let list_type =
+                    mkcref(MType::List(Located::new(element_type.clone(), loc.clone())));
+                list_type.unify(&array.type_)?;
+
+                let (loc, name) = match alias {
+                    Some(a) => (
+                        a.name
+                            .location()
+                            .as_ref()
+                            .map(|r| SourceLocation::from_file_range(file.clone(), Some(r.clone())))
+                            .unwrap_or(loc.clone()),
+                        a.name.clone(),
+                    ),
+                    None => (
+                        loc.clone(),
+                        param_ident(compiler.next_placeholder("unnest")?),
+                    ),
+                };
+
+                scope
+                    .write()?
+                    .add_reference(&name.get().into(), &loc, list_type)?;
+
+                compiler.async_cref(async move {
+                    let array = array.sql.await?;
+                    let array = array.read()?;
+                    let element_type = element_type.await?;
+                    let is_record = matches!(&*element_type.read()?, MType::Record(_));
+
+                    // DuckDB can't bind an UNNEST table factor that references the preceding
+                    // relations, so we use the same workaround as SQLBody::as_table and put the
+                    // unnest call in the projection of a lateral subquery. Lists of records are
+                    // splatted into their fields, while the elements of any other list are placed
+                    // in a column named after the alias, so that `t` refers to the element itself.
+                    //
+                    let body = SQLBody::Expr(array.body.as_expr());
+                    let (subquery, columns) = if is_record {
+                        match body.as_table(None) {
+                            sqlast::TableFactor::Derived { subquery, .. } => (subquery, Vec::new()),
+                            _ => {
+                                return Err(CompileError::internal(
+                                    loc.clone(),
+                                    "Expected unnested records to be a subquery",
+                                ))
+                            }
+                        }
+                    } else {
+                        (
+                            Box::new(select_no_from(
+                                sqlast::Expr::Function(sqlast::Function {
+                                    name: sqlast::ObjectName(vec![sqlast::Ident::new(
+                                        "unnest".to_string(),
+                                    )]),
+                                    args: vec![sqlast::FunctionArg::Unnamed(
+                                        sqlast::FunctionArgExpr::Expr(body.as_expr()),
+                                    )],
+                                    over: None,
+                                    distinct: false,
+                                    special: false,
+                                }),
+                                Some(name.clone()),
+                            )),
+                            vec![name.clone()],
+                        )
+                    };
+
+                    Ok(CSQLSnippet::wrap(
+                        array.names.clone(),
+                        sqlast::TableFactor::Derived {
+                            lateral: true,
+                            subquery,
+                            alias: Some(sqlast::TableAlias {
+                                name: name.clone(),
+                                columns,
+                            }),
+                        },
+                    ))
+                })?
             }
             sqlast::TableFactor::NestedJoin { .. } => {
                 return Err(CompileError::unimplemented(loc.clone(), "Nested JOIN"))
@@ -715,6 +940,149 @@ impl CompileSQL for sqlast::TableFactor {
     }
 }
 
+// A function call in the FROM clause (e.g. `FROM top_events(events, 10) t`) is compiled just
+// like any other function call, and its result (which must be a relation) is then used as the
+// table. Functions that are inlined or pushed down to SQL are spliced directly into the query,
+// while others are evaluated and scanned as a relation parameter.
+fn compile_table_function(
+    compiler: &Compiler,
+    schema: &Ref<Schema>,
+    scope: &Ref<SQLScope>,
+    loc: &SourceLocation,
+    name: &sqlast::ObjectName,
+    args: &Vec<sqlast::FunctionArg>,
+    alias: Option<&sqlast::TableAlias>,
+) -> Result<CRefSnippet<sqlast::TableFactor>> {
+    let call = sqlast::Expr::Function(sqlast::Function {
+        name: name.clone(),
+        args: args.clone(),
+        over: None,
+        distinct: false,
+        special: false,
+    });
+
+    // A table function call can't be LATERAL, so its arguments can't reference the relations that
+    // precede it in the FROM clause (the engine rejects them), only those of the enclosing query.
+    // Such calls can be wrapped in a lateral subquery instead.
+    //
+    let args_scope = SQLScope::new(scope.read()?.parent());
+    let relation = compile_sqlexpr(compiler.clone(), schema.clone(), args_scope, loc, &call)?;
+
+    let list_type = mkcref(MType::List(Located::new(
+        MType::new_unknown(format!("FROM {}(...)", name.to_string()).as_str()),
+        loc.clone(),
+    )));
+    list_type.unify(&relation.type_)?;
+
+    // Whether the call is a table function of the target engine depends on how the function is
+    // declared, not on the SQL it compiles to (an inlined function's body may be a function call
+    // that evaluates to a list, for example).
+    //
+    let file = schema.read()?.file.clone();
+    let (_, decl, remainder) = lookup_path::<ExprEntry>(
+        compiler.clone(),
+        schema.clone(),
+        &name.to_path(file),
+        true, /* import_global */
+        true, /* resolve_last */
+    )?;
+    let decl = match decl {
+        Some(decl) if remainder.is_empty() => Some(decl.value.clone()),
+        _ => None,
+    };
+
+    let name = match alias {
+        Some(a) => a.name.clone(),
+        None => name
+            .0
+            .last()
+            .ok_or_else(|| {
+                CompileError::internal(loc.clone(), "Function name must have at least one part")
+            })?
+            .clone(),
+    };
+
+    scope
+        .write()?
+        .add_reference(&name.get().into(), loc, relation.type_.clone())?;
+
+    let placeholder_name = QS_NAMESPACE.to_string() + compiler.next_placeholder("rel")?.as_str();
+    compiler.async_cref(async move {
+        let alias = Some(sqlast::TableAlias {
+            name: name.clone(),
+            columns: Vec::new(),
+        });
+
+        let table_function = match &decl {
+            Some(decl) => is_sql_table_function(decl).await?,
+            None => false,
+        };
+
+        Ok(match relation.expr.clone_inner().await? {
+            Expr::SQL(sql) => CSQLSnippet::wrap(
+                sql.names.clone(),
+                match &sql.body {
+                    SQLBody::Expr(sqlast::Expr::Function(f)) if table_function => {
+                        sqlast::TableFactor::Table {
+                            name: f.name.clone(),
+                            alias,
+                            args: Some(f.args.clone()),
+                            with_hints: Vec::new(),
+                        }
+                    }
+                    body => body.as_table(alias),
+                },
+            ),
+            expr => {
+                let mut names = CSQLNames::new();
+                names.params.insert(
+                    placeholder_name.clone().into(),
+                    TypedExpr {
+                        type_: relation.type_.clone(),
+                        expr: Arc::new(expr),
+                    },
+                );
+                CSQLSnippet::wrap(
+                    names,
+                    sqlast::TableFactor::Table {
+                        name: sqlast::ObjectName(vec![param_ident(placeholder_name)]),
+                        alias,
+                        args: None,
+                        with_hints: Vec::new(),
+                    },
+                )
+            }
+        })
+    })
+}
+
+// A function declared `= sql` whose declared return type is a relation is a table function in
+// the target engine.
+//
+async fn is_sql_table_function(decl: &ExprEntry) -> Result<bool> {
+    let expr = (&decl.expr).await?;
+    if !matches!(
+        &*expr.read()?,
+        Expr::Fn(FnExpr {
+            body: FnBody::SQLBuiltin,
+            ..
+        })
+    ) {
+        return Ok(false);
+    }
+
+    let stype = (&decl.type_).await?;
+    let body = stype.read()?.body.clone();
+    let fn_type = body.await?;
+    let ret = match &*fn_type.read()? {
+        MType::Fn(f) => f.ret.clone(),
+        _ => return Ok(false),
+    };
+    let ret = ret.await?;
+    let is_relation = matches!(&*ret.read()?, MType::List(_));
+    Ok(is_relation)
+}
+
 impl CompileSQL for sqlast::JoinConstraint {
     fn compile_sql(
         &self,
@@ -795,7 +1163,22 @@ impl CompileSQL for sqlast::Join {
         scope: &Ref<SQLScope>,
         loc: &SourceLocation,
     ) -> Result<CRefSnippet<Self>> {
+        let before = scope.read()?.relation_names();
         let relation = self.relation.compile_sql(compiler, schema, scope, loc)?;
+
+        // The fields of relations on the outer side of an outer join may be NULL, regardless of
+        // their declared types.
+        let joined = scope.read()?.relation_names();
+        let nullable = match &self.join_operator {
+            sqlast::JoinOperator::LeftOuter(_) => joined.difference(&before).cloned().collect(),
+            sqlast::JoinOperator::RightOuter(_) => before,
+            sqlast::JoinOperator::FullOuter(_) => joined,
+            _ => BTreeSet::new(),
+        };
+        for name in nullable.iter() {
+            scope.write()?.set_nullable(name);
+        }
+
         let join_operator = self
             .join_operator
             .compile_sql(compiler, schema, scope, loc)?;
@@ -1013,16 +1396,16 @@ pub fn compile_select(
         return Err(CompileError::unimplemented(loc.clone(), "SORT BY"));
     }
 
-    if select.having.is_some() {
-        return Err(CompileError::unimplemented(loc.clone(), "HAVING"));
-    }
-
     if select.qualify.is_some() {
         return Err(CompileError::unimplemented(loc.clone(), "QUALIFY"));
     }
 
     let (scope, from) = compile_from(&compiler, &schema, parent_scope.clone(), loc, &select.from)?;
 
+    // Aggregates may be called in the SELECT list (as well as HAVING and ORDER BY), but not in
+    // WHERE or GROUP BY.
+    let agg_scope = scope.read()?.with_aggregates();
+
     let exprs = select
         .projection
         .iter()
@@ -1041,8 +1424,13 @@ pub fn compile_select(
                             .into(),
                         _ => format!("{}", expr).into(),
                     };
-                    let compiled =
-                        compile_sqlarg(compiler.clone(), schema.clone(), scope.clone(), loc, expr)?;
+                    let compiled = compile_sqlarg(
+                        compiler.clone(),
+                        schema.clone(),
+                        agg_scope.clone(),
+                        loc,
+                        expr,
+                    )?;
                     mkcref(vec![CTypedNameAndSQL {
                         name: Ident::with_location(loc.clone(), name),
                         type_: compiled.type_,
@@ -1050,8 +1438,13 @@ pub fn compile_select(
                     }])
                 }
                 sqlast::SelectItem::ExprWithAlias { expr, alias } => {
-                    let compiled =
-                        compile_sqlarg(compiler.clone(), schema.clone(), scope.clone(), loc, expr)?;
+                    let compiled = compile_sqlarg(
+                        compiler.clone(),
+                        schema.clone(),
+                        agg_scope.clone(),
+                        loc,
+                        expr,
+                    )?;
                     mkcref(vec![CTypedNameAndSQL {
                         name: Ident::from_sqlident(loc.clone(), alias.get().clone()),
                         type_: compiled.type_,
@@ -1125,18 +1518,49 @@ pub fn compile_select(
 
     let projections = combine_crefs(exprs)?;
 
-    let type_: CRef<MType> = projections.then({
+    let having = match &select.having {
+        Some(having) => {
+            let compiled = compile_sqlarg(
+                compiler.clone(),
+                schema.clone(),
+                agg_scope.clone(),
+                loc,
+                having,
+            )?;
+            compiled
+                .type_
+                .unify(&resolve_global_atom(compiler.clone(), "bool")?)?;
+            Some(compiled.sql)
+        }
+        None => None,
+    };
+
+    let available = scope
+        .read()?
+        .get_available_references(compiler.clone(), loc, None)?;
+    let type_: CRef<MType> = compiler.async_cref({
+        let projections = projections.clone();
         let loc = loc.clone();
-        move |exprs: Ref<Vec<Ref<Vec<CTypedNameAndSQL>>>>| {
+        async move {
+            let exprs = projections.await?;
+            let items = exprs
+                .read()?
+                .iter()
+                .map(|a| Ok(a.read()?.clone()))
+                .collect::<Result<Vec<_>>>()?
+                .concat();
+            let available = available.await?;
+
             let mut fields = Vec::new();
-            for a in &*exprs.read()? {
-                for b in &*a.read()? {
-                    fields.push(MField {
-                        name: b.name.get().clone(),
-                        type_: b.type_.clone(),
-                        nullable: true,
-                    });
-                }
+            for item in items {
+                let sql = item.sql.clone().await?;
+                let nullable = sqlexpr_nullable(&sql.read()?.body.as_expr(), &*available.read()?);
+                fields.push(MField {
+                    name: item.name.get().clone(),
+                    type_: item.type_.clone(),
+                    nullable,
+                    loc: item.name.location().clone(),
+                });
             }
 
             Ok(mkcref(MType::List(Located::new(
@@ -1209,11 +1633,21 @@ pub fn compile_select(
                 group_by.push(sql.body.as_expr());
             }
 
+            let having = match having {
+                Some(having) => {
+                    let sql = having.await?.read()?.clone();
+                    names.extend(sql.names.clone());
+                    Some(sql.body.as_expr())
+                }
+                None => None,
+            };
+
             let mut ret = select.clone();
             ret.from = from.body;
             ret.projection = projection;
             ret.selection = selection;
             ret.group_by = group_by;
+            ret.having = having;
 
             let names = scope
                 .read()?
@@ -1306,12 +1740,19 @@ pub fn compile_sqlquery(
     let (scope, type_, set_expr) =
         compile_setexpr(&compiler, &schema, parent_scope.clone(), loc, &query.body)?;
 
+    // The ORDER BY of a plain SELECT may call aggregates, just like its SELECT list.
+    //
+    let order_by_scope = match query.body.as_ref() {
+        sqlast::SetExpr::Select(_) => scope.read()?.with_aggregates(),
+        _ => scope.clone(),
+    };
+
     Ok((
         scope.clone(),
         type_,
         compiler.async_cref({
             let compiled_order_by =
-                compile_order_by(&compiler, &schema, &scope, loc, &query.order_by)?;
+                compile_order_by(&compiler, &schema, &order_by_scope, loc, &query.order_by)?;
 
             let loc = loc.clone();
             let compiler = compiler.clone();
@@ -1487,7 +1928,7 @@ fn apply_sqlcast(
 
 fn coerce_all(
     compiler: &Compiler,
-    op: &sqlparser::ast::BinaryOperator,
+    op: &CoerceOp,
     args: Vec<CTypedSQL>,
     unknown_debug_name: &str,
 ) -> Result<(CRef<MType>, Vec<CTypedSQL>)> {
@@ -1500,12 +1941,7 @@ fn coerce_all(
         target = first.type_.clone();
         for next in iter {
             exprs.push(next.clone());
-            target = coerce(
-                compiler.clone(),
-                CoerceOp::Binary(op.clone()),
-                target,
-                next.type_.clone(),
-            )?;
+            target = coerce(compiler.clone(), op.clone(), target, next.type_.clone())?;
         }
     }
 
@@ -1523,7 +1959,12 @@ fn coerce_all(
                 let their_type = resolved_target.read()?;
 
                 Ok(
-                    if their_type.to_runtime_type().context(RuntimeSnafu {
+                    // Union types can't be written as the target of a cast, so values that are
+                    // coerced to a union (i.e. values of one of its members' types) are passed
+                    // as is, and the engine converts them implicitly.
+                    if matches!(&*their_type, MType::Union(_)) {
+                        arg.sql
+                    } else if their_type.to_runtime_type().context(RuntimeSnafu {
                         loc: their_type.location(),
                     })? != my_type.to_runtime_type().context(RuntimeSnafu {
                         loc: my_type.location(),
@@ -1540,48 +1981,645 @@ fn coerce_all(
     Ok((target, ret))
 }
 
-pub fn unify_all<T, C, I>(mut iter: I, unknown_debug_name: &str) -> Result<CRef<T>>
-where
-    T: Constrainable + 'static,
-    C: HasCType<T>,
-    I: Iterator<Item = C>,
-{
-    if let Some(first) = iter.next() {
-        for next in iter {
-            first.type_().unify(&next.type_())?;
-        }
-        Ok(first.type_().clone())
-    } else {
-        Ok(CRef::new_unknown(unknown_debug_name))
-    }
+// A function call whose arguments have been matched to the parameters of the called function.
+//
+#[derive(Clone, Debug)]
+struct FnCallBinding {
+    func: TypedExpr<CRef<MType>>,
+    fn_type: Located<MFnType>,
+    args: Vec<CTypedNameAndExpr>,
 }
 
-pub fn combine_sql_exprs<'a, I>(iter: I, names: &mut CSQLNames) -> Result<Vec<sqlast::Expr>>
-where
-    I: Iterator<Item = &'a Ref<SQL<CRef<MType>>>>,
-{
-    iter.map(|c| {
-        let c = c.read()?;
-        names.extend(c.names.clone());
-        Ok(c.body.as_expr())
-    })
-    .collect::<Result<Vec<_>>>()
-}
+impl Constrainable for FnCallBinding {}
 
-pub fn has_unbound_names(expr: Arc<Expr<CRef<MType>>>) -> bool {
-    match expr.as_ref() {
-        Expr::SQL(e) => {
-            !e.names.unbound.is_empty()
-                || e.names
-                    .params
-                    .iter()
-                    .any(|(_, p)| has_unbound_names(p.expr.clone()))
+// Compiles a reference to each overload of the function at `path`. Most functions are not
+// overloaded, in which case this is just a reference to the function itself.
+//
+fn compile_overloads(
+    compiler: Compiler,
+    schema: Ref<Schema>,
+    path: &ast::Path,
+) -> Result<Vec<TypedExpr<CRef<MType>>>> {
+    let (declared, decl, remainder) = lookup_path::<ExprEntry>(
+        compiler.clone(),
+        schema.clone(),
+        path,
+        true, /* import_global */
+        true, /* resolve_last */
+    )?;
+    let (decl, overloads) = match decl {
+        Some(decl) if remainder.is_empty() => {
+            let overloads = declared
+                .read()?
+                .overloads
+                .get(decl.name.get())
+                .into_iter()
+                .flatten()
+                // Overloads reached through another schema must be exported, like the function.
+                .filter(|overload| path.len() == 1 || overload.public)
+                .map(|overload| overload.get().clone())
+                .collect::<Vec<_>>();
+            (decl, overloads)
         }
-        _ => false,
+        _ => return Ok(vec![compile_reference(compiler, schema, path)?]),
+    };
+
+    std::iter::once(decl)
+        .chain(overloads.into_iter())
+        .map(|decl| compile_decl_reference(compiler.clone(), path, &decl, Vec::new()))
+        .collect()
+}
+
+fn fn_type_of(func: &TypedExpr<CRef<MType>>, loc: &SourceLocation) -> Result<Located<MFnType>> {
+    let type_ = func
+        .type_
+        .must()
+        .context(RuntimeSnafu { loc: loc.clone() })?;
+    let type_ = type_.read()?;
+    match &*type_ {
+        MType::Fn(f) => Ok(f.clone()),
+        _ => Err(CompileError::wrong_type(
+            &MType::Fn(Located::new(
+                MFnType {
+                    args: Vec::new(),
+                    ret: MType::new_unknown("ret"),
+                    anonymous: false,
+                },
+                loc.clone(),
+            )),
+            &*type_,
+        )),
     }
 }
 
-pub fn schema_infer_load_fn(
+// Matches the (positional and named) arguments of a call to the function's parameters, and
+// unifies their types. Missing arguments take their default values.
+//
+fn bind_fn_args(
+    compiler: Compiler,
+    func: &TypedExpr<CRef<MType>>,
+    fn_type: &MFnType,
+    call_args: &Vec<(Option<Located<Ident>>, CTypedExpr)>,
+    loc: &SourceLocation,
+) -> Result<Vec<CTypedNameAndExpr>> {
+    let mut compiled_args: BTreeMap<Ident, CTypedNameAndExpr> = BTreeMap::new();
+    let mut pos: usize = 0;
+    for (name, arg) in call_args {
+        let name = match name {
+            Some(name) => name.clone(),
+            None => {
+                if pos >= fn_type.args.len() {
+                    return Err(CompileError::no_such_entry(vec![Ident::with_location(
+                        loc.clone(),
+                        format!("argument {}", pos),
+                    )]));
+                }
+                pos += 1;
+                Ident::with_location(loc.clone(), fn_type.args[pos - 1].name.clone())
+            }
+        };
+
+        if compiled_args.get(&name).is_some() {
+            return Err(CompileError::duplicate_entry(vec![name]));
+        }
+
+        compiled_args.insert(
+            name.get().clone(),
+            CTypedNameAndExpr {
+                name: name.get().clone(),
+                type_: arg.type_.clone(),
+                expr: arg.expr.clone(),
+            },
+        );
+    }
+
+    let mut arg_exprs = Vec::new();
+    for arg in &fn_type.args {
+        if let Some(compiled_arg) = compiled_args.get_mut(&arg.name) {
+            arg.type_.unify(&compiled_arg.type_)?;
+            arg_exprs.push(compiled_arg.clone());
+        } else {
+            // If the argument is missing, then use the default value declared by the
+            // function, if any. Otherwise, nullable arguments are set to NULL.
+            let type_ = MType::new_unknown(&format!("default {}", arg.name));
+            arg_exprs.push(CTypedNameAndExpr {
+                name: arg.name.clone(),
+                type_: type_.clone(),
+                expr: default_arg(compiler.clone(), func, arg, type_)?,
+            });
+        }
+    }
+
+    Ok(arg_exprs)
+}
+
+// Picks the overload of a function that accepts the types of the call's arguments, and binds the
+// arguments to it. If several overloads accept them, then the one whose parameter types match the
+// most arguments exactly (i.e. not through generics) wins.
+//
+fn resolve_overload(
+    compiler: Compiler,
+    loc: &SourceLocation,
+    func_name: &ast::Path,
+    candidates: Vec<TypedExpr<CRef<MType>>>,
+    call_args: Vec<(Option<Located<Ident>>, CTypedExpr)>,
+    type_: CRef<MType>,
+) -> Result<CRef<FnCallBinding>> {
+    let loc = loc.clone();
+    let func_name = func_name.clone();
+    compiler.clone().async_cref(async move {
+        // Arguments whose types aren't known yet (e.g. because they're only inferred from the
+        // overload that's chosen) are compatible with any parameter, so the overloads are ranked by
+        // the other arguments. If that's ambiguous, then we wait for the rest of the argument types
+        // to be inferred and rank them again.
+        //
+        let (arg_types, mut arg_type_names) = overload_arg_types(&call_args, false).await?;
+        let mut best = rank_overloads(&loc, &candidates, &call_args, &arg_types).await?;
+        if best.len() > 1 && !all_known(&call_args)? {
+            let (arg_types, names) = overload_arg_types(&call_args, true).await?;
+            best = rank_overloads(&loc, &candidates, &call_args, &arg_types).await?;
+            arg_type_names = names;
+        }
+
+        let (func, fn_type) = match best.len() {
+            1 => best.into_iter().next().unwrap(),
+            0 => {
+                return Err(CompileError::overload(
+                    loc.clone(),
+                    func_name,
+                    &format!(
+                        "no overload accepts arguments of types ({})",
+                        arg_type_names.join(", ")
+                    ),
+                ))
+            }
+            _ => {
+                return Err(CompileError::overload(
+                    loc.clone(),
+                    func_name,
+                    &format!(
+                        "the call is ambiguous between the overloads declared at {}",
+                        best.iter()
+                            .map(|(_, fn_type)| fn_type.location().pretty())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ))
+            }
+        };
+
+        let args = bind_fn_args(compiler.clone(), &func, &fn_type, &call_args, &loc)?;
+        type_.unify(&fn_type.ret)?;
+        Ok(mkcref(FnCallBinding {
+            func,
+            fn_type,
+            args,
+        }))
+    })
+}
+
+fn all_known(call_args: &Vec<(Option<Located<Ident>>, CTypedExpr)>) -> Result<bool> {
+    for (_, arg) in call_args {
+        if !arg.type_.is_known()? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// The runtime types of a call's arguments, for ranking overloads, along with their names (for
+// errors). NULLs and arguments whose types aren't known (unless `wait` is set) have no type, since
+// they're compatible with any parameter.
+//
+async fn overload_arg_types(
+    call_args: &Vec<(Option<Located<Ident>>, CTypedExpr)>,
+    wait: bool,
+) -> Result<(Vec<Option<Type>>, Vec<String>)> {
+    let mut arg_types = Vec::new();
+    let mut arg_type_names = Vec::new();
+    for (_, arg) in call_args {
+        if !wait && !arg.type_.is_known()? {
+            arg_types.push(None);
+            arg_type_names.push("unknown".to_string());
+            continue;
+        }
+
+        let arg_type = arg.type_.clone().await?;
+        let arg_type = arg_type.read()?;
+        arg_types.push(match arg_type.to_runtime_type() {
+            Ok(Type::Atom(AtomicType::Null)) => None,
+            t => t.ok(),
+        });
+        arg_type_names.push(arg_type.pretty());
+    }
+    Ok((arg_types, arg_type_names))
+}
+
+// The overloads that accept a call's arguments and have the best score.
+//
+async fn rank_overloads(
+    loc: &SourceLocation,
+    candidates: &Vec<TypedExpr<CRef<MType>>>,
+    call_args: &Vec<(Option<Located<Ident>>, CTypedExpr)>,
+    arg_types: &Vec<Option<Type>>,
+) -> Result<Vec<(TypedExpr<CRef<MType>>, MFnType)>> {
+    let mut best = Vec::new();
+    let mut best_score = 0;
+    for func in candidates {
+        let fn_type = match &*func.type_.clone().await?.read()? {
+            MType::Fn(f) => f.clone(),
+            _ => continue,
+        };
+        let defaults = match func.expr.unwrap_schema_entry().await?.as_ref() {
+            Expr::Fn(FnExpr { inner_schema, .. }) => {
+                inner_schema.read()?.arg_defaults.keys().cloned().collect()
+            }
+            _ => BTreeSet::new(),
+        };
+
+        let score = match overload_score(loc, &fn_type, &defaults, call_args, arg_types)? {
+            Some(score) => score,
+            None => continue,
+        };
+        if best.is_empty() || score > best_score {
+            best = vec![(func.clone(), fn_type)];
+            best_score = score;
+        } else if score == best_score {
+            best.push((func.clone(), fn_type));
+        }
+    }
+    Ok(best)
+}
+
+// Returns how well an overload matches the arguments of a call (the number of arguments whose
+// types match a parameter's type exactly), or None if it does not accept them at all.
+//
+fn overload_score(
+    loc: &SourceLocation,
+    fn_type: &MFnType,
+    defaults: &BTreeSet<Ident>,
+    call_args: &Vec<(Option<Located<Ident>>, CTypedExpr)>,
+    arg_types: &Vec<Option<Type>>,
+) -> Result<Option<usize>> {
+    let mut bound = BTreeMap::new();
+    let mut pos = 0;
+    for ((name, _), arg_type) in call_args.iter().zip(arg_types.iter()) {
+        let param = match name {
+            Some(name) => fn_type.args.iter().find(|a| &a.name == name.get()),
+            None => {
+                pos += 1;
+                fn_type.args.get(pos - 1)
+            }
+        };
+        let param = match param {
+            Some(param) => param,
+            None => return Ok(None),
+        };
+        if bound.insert(param.name.clone(), arg_type).is_some() {
+            return Ok(None);
+        }
+    }
+
+    let mut score = 0;
+    for param in &fn_type.args {
+        let arg_type = match bound.get(&param.name) {
+            Some(arg_type) => arg_type,
+            None if param.nullable || defaults.contains(&param.name) => continue,
+            None => return Ok(None),
+        };
+
+        // Generic parameters (whose types are not known yet) accept any argument.
+        let param_type = match param.type_.is_known()? {
+            true => param
+                .type_
+                .must()
+                .context(RuntimeSnafu { loc: loc.clone() })?
+                .read()?
+                .to_runtime_type()
+                .ok(),
+            false => None,
+        };
+        match (param_type, arg_type) {
+            (Some(param_type), Some(arg_type)) => {
+                if !param_type.eq_ignoring_nullability(arg_type) {
+                    return Ok(None);
+                }
+                score += 1;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Some(score))
+}
+
+// Computes the value of an argument that is missing from a function call, once the function's
+// definition is known. `type_` is unified with the type of the resulting value.
+//
+fn default_arg(
+    compiler: Compiler,
+    func: &TypedExpr<CRef<MType>>,
+    arg: &MField,
+    type_: CRef<MType>,
+) -> Result<CRef<Expr<CRef<MType>>>> {
+    let func = func.expr.clone();
+    let arg = arg.clone();
+    compiler.async_cref(async move {
+        let default = match func.unwrap_schema_entry().await?.as_ref() {
+            Expr::Fn(FnExpr { inner_schema, .. }) => {
+                inner_schema.read()?.arg_defaults.get(&arg.name).cloned()
+            }
+            _ => None,
+        };
+
+        let default = match default {
+            Some(default) => {
+                arg.type_.unify(&default.type_)?;
+                default
+            }
+            None if arg.nullable => NULL.clone(),
+            None => {
+                return Err(CompileError::missing_arg(vec![Ident::without_location(
+                    arg.name.clone(),
+                )]))
+            }
+        };
+
+        type_.unify(&default.type_)?;
+        Ok(default.expr)
+    })
+}
+
+// Infers whether a (compiled) SQL expression may evaluate to NULL, based on the nullability of the
+// fields it references. Anything we don't know about is assumed to be nullable.
+//
+pub fn sqlexpr_nullable(expr: &sqlast::Expr, available: &AvailableReferences) -> bool {
+    use sqlast::Expr::*;
+    let nullable = |e: &sqlast::Expr| sqlexpr_nullable(e, available);
+    match expr {
+        Value(sqlast::Value::Null) | Value(sqlast::Value::Placeholder(_)) => true,
+        Value(_) => false,
+        Identifier(ident) => match available.get(&ident.get().into()) {
+            Some(m) if m.type_.is_some() => m.nullable,
+            _ => true,
+        },
+        CompoundIdentifier(path) if path.len() == 2 => {
+            let (relation, field): (Ident, Ident) = (path[0].get().into(), path[1].get().into());
+            match available.get(&field) {
+                Some(m) if m.type_.is_some() && m.relation.get() == &relation => m.nullable,
+                _ => true,
+            }
+        }
+        IsNull(_) | IsNotNull(_) | IsDistinctFrom(..) | IsNotDistinctFrom(..) => false,
+        Nested(e) | UnaryOp { expr: e, .. } | Cast { expr: e, .. } => nullable(e),
+        BinaryOp { left, right, .. } => nullable(left) || nullable(right),
+        Like { expr, pattern, .. }
+        | ILike { expr, pattern, .. }
+        | SimilarTo { expr, pattern, .. } => nullable(expr) || nullable(pattern),
+        Case {
+            results,
+            else_result,
+            ..
+        } => match else_result {
+            Some(else_result) => nullable(else_result) || results.iter().any(|r| nullable(r)),
+            None => true,
+        },
+        Function(f) => {
+            let args = f
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    sqlast::FunctionArg::Named {
+                        arg: sqlast::FunctionArgExpr::Expr(e),
+                        ..
+                    }
+                    | sqlast::FunctionArg::Unnamed(sqlast::FunctionArgExpr::Expr(e)) => nullable(e),
+                    _ => true,
+                })
+                .collect::<Vec<_>>();
+            match f.name.to_string().to_lowercase().as_str() {
+                // COALESCE is only NULL if all of its arguments are
+                "coalesce" => args.iter().all(|a| *a),
+                // COUNT is never NULL, but other aggregates are NULL over an empty input
+                "count" => false,
+                _ => true,
+            }
+        }
+        _ => true,
+    }
+}
+
+// Computes the result type of a unary operator, once the type of its argument is known.
+//
+fn unary_coerce(
+    compiler: &Compiler,
+    loc: &SourceLocation,
+    op: &sqlast::UnaryOperator,
+    arg: &CRef<MType>,
+) -> Result<CRef<MType>> {
+    let (loc, op, arg) = (loc.clone(), op.clone(), arg.clone());
+    compiler.async_cref(async move {
+        let arg = arg.await?;
+        let arg = arg.read()?;
+        let arg_rt = arg.to_runtime_type().context(RuntimeSnafu {
+            loc: arg.location(),
+        })?;
+
+        match unary_coerce_type(&op, &arg_rt) {
+            Some(t) => Ok(mkcref(MType::from_runtime_type(&t)?)),
+            None => {
+                // The operand is reported against a type that the operator does accept.
+                use sqlast::UnaryOperator::*;
+                let expected = match op {
+                    Not => AtomicType::Boolean,
+                    PGBitwiseNot | PGPostfixFactorial | PGPrefixFactorial => AtomicType::Int64,
+                    _ => AtomicType::Float64,
+                };
+                Err(CompileError::wrong_type(
+                    &MType::Atom(Located::new(expected, loc)),
+                    &*arg,
+                ))
+            }
+        }
+    })
+}
+
+// When an enum is compared with string literals, the literals must be members of the enum, since
+// otherwise the comparison can never be true (e.g. because of a typo). The check runs once the
+// types of the arguments are known, and resolves to `type_`.
+//
+fn check_enum_literals(
+    compiler: &Compiler,
+    loc: &SourceLocation,
+    args: Vec<(sqlast::Expr, CRef<MType>)>,
+    type_: CRef<MType>,
+) -> Result<CRef<MType>> {
+    let loc = loc.clone();
+    let check_compiler = compiler.clone();
+    compiler.async_cref(async move {
+        let mut members = None;
+        for (_, arg_type) in args.iter() {
+            let arg_type = arg_type.clone().await?;
+            let arg_members = match &*arg_type.read()? {
+                MType::Atom(atom) => match atom.get() {
+                    AtomicType::Enum(members) => Some(members.clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            members = members.or(arg_members);
+        }
+
+        // An enum without members (e.g. one that was read from the engine) can't be checked.
+        //
+        if let Some(members) = members.filter(|members| !members.is_empty()) {
+            for (expr, _) in args.iter() {
+                match expr {
+                    sqlast::Expr::Value(sqlast::Value::SingleQuotedString(value))
+                        if !members.contains(value) =>
+                    {
+                        return Err(CompileError::enum_member(
+                            literal_location(&check_compiler, &loc, value)?,
+                            value,
+                            &members,
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(type_)
+    })
+}
+
+// Literals don't carry their own locations in the parse tree, so a string literal is located by
+// finding it in the source text that `loc` (the location of the expression it's a part of)
+// spans. If it can't be found, `loc` is used instead.
+//
+fn literal_location(
+    compiler: &Compiler,
+    loc: &SourceLocation,
+    value: &str,
+) -> Result<SourceLocation> {
+    let (file, range) = match loc {
+        SourceLocation::Range(file, range) => (file, range),
+        _ => return Ok(loc.clone()),
+    };
+
+    let data = compiler.file_contents()?;
+    let contents = match data.files.get(file) {
+        Some(contents) => contents,
+        None => return Ok(loc.clone()),
+    };
+
+    let literal = format!("'{}'", value.replace('\'', "''"));
+    for (idx, line) in contents.lines().enumerate() {
+        let line_number = idx as u64 + 1;
+        if line_number < range.start.line || line_number > range.end.line {
+            continue;
+        }
+
+        for (offset, _) in line.match_indices(literal.as_str()) {
+            let column = line[..offset].chars().count() as u64 + 1;
+            let start = Location {
+                line: line_number,
+                column,
+            };
+            if loc.contains(&start) {
+                return Ok(SourceLocation::Range(
+                    file.clone(),
+                    Range {
+                        start,
+                        end: Location {
+                            line: line_number,
+                            column: column + literal.chars().count() as u64,
+                        },
+                    },
+                ));
+            }
+        }
+    }
+
+    Ok(loc.clone())
+}
+
+fn check_engine_operator(compiler: &Compiler, loc: &SourceLocation, op: &str) -> Result<()> {
+    let engine = compiler.engine()?;
+    if engine.supports_operator(op) {
+        Ok(())
+    } else {
+        Err(CompileError::unimplemented(
+            loc.clone(),
+            format!("Operator {} with the {:?} engine", op, engine).as_str(),
+        ))
+    }
+}
+
+fn combine_binary_sqlexpr<F>(
+    left: &CTypedSQL,
+    right: &CTypedSQL,
+    constructor: F,
+) -> Result<CRef<Expr<CRef<MType>>>>
+where
+    F: Fn(Box<sqlast::Expr>, Box<sqlast::Expr>) -> sqlast::Expr + Clone + Send + Sync + 'static,
+{
+    combine_crefs(vec![left.sql.clone(), right.sql.clone()])?.then({
+        move |args: Ref<Vec<Ref<SQL<CRef<MType>>>>>| {
+            let names = combine_sqlnames(&*args.read()?)?;
+            Ok(mkcref(Expr::SQL(Arc::new(SQL {
+                names,
+                body: SQLBody::Expr(constructor(
+                    Box::new(args.read()?[0].read()?.body.as_expr()),
+                    Box::new(args.read()?[1].read()?.body.as_expr()),
+                )),
+            }))))
+        }
+    })
+}
+
+pub fn unify_all<T, C, I>(mut iter: I, unknown_debug_name: &str) -> Result<CRef<T>>
+where
+    T: Constrainable + 'static,
+    C: HasCType<T>,
+    I: Iterator<Item = C>,
+{
+    if let Some(first) = iter.next() {
+        for next in iter {
+            first.type_().unify(&next.type_())?;
+        }
+        Ok(first.type_().clone())
+    } else {
+        Ok(CRef::new_unknown(unknown_debug_name))
+    }
+}
+
+pub fn combine_sql_exprs<'a, I>(iter: I, names: &mut CSQLNames) -> Result<Vec<sqlast::Expr>>
+where
+    I: Iterator<Item = &'a Ref<SQL<CRef<MType>>>>,
+{
+    iter.map(|c| {
+        let c = c.read()?;
+        names.extend(c.names.clone());
+        Ok(c.body.as_expr())
+    })
+    .collect::<Result<Vec<_>>>()
+}
+
+pub fn has_unbound_names(expr: Arc<Expr<CRef<MType>>>) -> bool {
+    match expr.as_ref() {
+        Expr::SQL(e) => {
+            !e.names.unbound.is_empty()
+                || e.names
+                    .params
+                    .iter()
+                    .any(|(_, p)| has_unbound_names(p.expr.clone()))
+        }
+        _ => false,
+    }
+}
+
+pub fn schema_infer_load_fn(
     schema: SchemaRef,
     args: Vec<TypedNameAndExpr<CRef<MType>>>,
     inner_type: CRef<MType>,
@@ -1850,11 +2888,8 @@ pub fn compile_sqlexpr(
                 }))),
             },
             sqlast::Value::Null => NULL.clone(),
-            sqlast::Value::Placeholder(_) => {
-                return Err(CompileError::unimplemented(
-                    loc.clone(),
-                    format!("SQL Parameter syntax: {}", expr).as_str(),
-                ))
+            sqlast::Value::Placeholder(placeholder) => {
+                compile_placeholder(compiler.clone(), schema.clone(), loc, placeholder)?
             }
         },
         sqlast::Expr::Array(sqlast::Array { elem, .. }) => {
@@ -1925,30 +2960,49 @@ pub fn compile_sqlexpr(
                 right.as_ref(),
             )?;
             use sqlast::BinaryOperator::*;
+            match op {
+                PGBitwiseXor | PGBitwiseShiftLeft | PGBitwiseShiftRight => {
+                    check_engine_operator(&compiler, loc, &op.to_string())?
+                }
+                _ => {}
+            };
+            let coerce_op = CoerceOp::Binary(op.clone());
             let type_ = match op {
-                Plus | Minus | Multiply | Divide => {
+                // The result of these operators is the type that both sides are coerced to
+                Plus | Minus | Multiply | Divide | Modulo | StringConcat | BitwiseOr
+                | BitwiseAnd | BitwiseXor | PGBitwiseXor | PGBitwiseShiftLeft
+                | PGBitwiseShiftRight => {
                     let (result_type, casted) = coerce_all(
                         &compiler,
-                        &op,
+                        &coerce_op,
                         vec![cleft, cright],
                         format!("{:?}", op).as_str(),
                     )?;
                     (cleft, cright) = (casted[0].clone(), casted[1].clone());
                     result_type
                 }
-                Eq | NotEq | Lt | LtEq | Gt | GtEq => {
+                Eq | NotEq | Lt | LtEq | Gt | GtEq | PGRegexMatch | PGRegexIMatch
+                | PGRegexNotMatch | PGRegexNotIMatch => {
+                    let args = vec![
+                        (left.as_ref().clone(), cleft.type_.clone()),
+                        (right.as_ref().clone(), cright.type_.clone()),
+                    ];
                     let (_, casted) = coerce_all(
                         &compiler,
-                        &op,
+                        &coerce_op,
                         vec![cleft, cright],
                         format!("{:?}", op).as_str(),
                     )?;
                     (cleft, cright) = (casted[0].clone(), casted[1].clone());
-                    resolve_global_atom(compiler.clone(), "bool")?
+
+                    let bool_type = resolve_global_atom(compiler.clone(), "bool")?;
+                    match op {
+                        Eq | NotEq => check_enum_literals(&compiler, loc, args, bool_type)?,
+                        _ => bool_type,
+                    }
                 }
 
-                And | Or | Xor | BitwiseOr | BitwiseAnd | BitwiseXor | PGBitwiseXor
-                | PGBitwiseShiftLeft | PGBitwiseShiftRight => {
+                And | Or | Xor => {
                     let bool_val = CTypedSQL {
                         type_: resolve_global_atom(compiler.clone(), "bool")?,
                         sql: mkcref(SQL {
@@ -1958,7 +3012,7 @@ pub fn compile_sqlexpr(
                     };
                     let (_, casted) = coerce_all(
                         &compiler,
-                        &op,
+                        &coerce_op,
                         vec![cleft, cright, bool_val],
                         format!("{:?}", op).as_str(),
                     )?;
@@ -1974,17 +3028,121 @@ pub fn compile_sqlexpr(
             };
             CTypedExpr {
                 type_,
-                expr: combine_crefs(vec![cleft.sql, cright.sql])?.then({
-                    move |args: Ref<Vec<Ref<SQL<CRef<MType>>>>>| {
-                        let names = combine_sqlnames(&*args.read()?)?;
-                        Ok(mkcref(Expr::SQL(Arc::new(SQL {
-                            names,
-                            body: SQLBody::Expr(sqlast::Expr::BinaryOp {
-                                left: Box::new(args.read()?[0].read()?.body.as_expr()),
-                                op: op.clone(),
-                                right: Box::new(args.read()?[1].read()?.body.as_expr()),
-                            }),
-                        }))))
+                expr: combine_binary_sqlexpr(&cleft, &cright, move |left, right| {
+                    sqlast::Expr::BinaryOp {
+                        left,
+                        op: op.clone(),
+                        right,
+                    }
+                })?,
+            }
+        }
+        sqlast::Expr::Like {
+            negated,
+            expr: e,
+            pattern,
+            escape_char,
+        }
+        | sqlast::Expr::ILike {
+            negated,
+            expr: e,
+            pattern,
+            escape_char,
+        }
+        | sqlast::Expr::SimilarTo {
+            negated,
+            expr: e,
+            pattern,
+            escape_char,
+        } => {
+            let (_, casted) = coerce_all(
+                &compiler,
+                &CoerceOp::Like,
+                vec![c_sqlarg(e)?, c_sqlarg(pattern)?],
+                "like",
+            )?;
+
+            let (negated, escape_char) = (*negated, escape_char.clone());
+            let like = expr.clone();
+            CTypedExpr {
+                type_: resolve_global_atom(compiler.clone(), "bool")?,
+                expr: combine_binary_sqlexpr(&casted[0], &casted[1], move |expr, pattern| {
+                    match &like {
+                        sqlast::Expr::Like { .. } => sqlast::Expr::Like {
+                            negated,
+                            expr,
+                            pattern,
+                            escape_char,
+                        },
+                        sqlast::Expr::ILike { .. } => sqlast::Expr::ILike {
+                            negated,
+                            expr,
+                            pattern,
+                            escape_char,
+                        },
+                        _ => sqlast::Expr::SimilarTo {
+                            negated,
+                            expr,
+                            pattern,
+                            escape_char,
+                        },
+                    }
+                })?,
+            }
+        }
+        sqlast::Expr::IsDistinctFrom(left, right)
+        | sqlast::Expr::IsNotDistinctFrom(left, right) => {
+            let (cleft, cright) = (c_sqlarg(left)?, c_sqlarg(right)?);
+            let args = vec![
+                (left.as_ref().clone(), cleft.type_.clone()),
+                (right.as_ref().clone(), cright.type_.clone()),
+            ];
+            let (_, casted) = coerce_all(
+                &compiler,
+                &CoerceOp::IsDistinctFrom,
+                vec![cleft, cright],
+                "is distinct from",
+            )?;
+
+            let constructor = match expr {
+                sqlast::Expr::IsDistinctFrom(..) => sqlast::Expr::IsDistinctFrom,
+                sqlast::Expr::IsNotDistinctFrom(..) => sqlast::Expr::IsNotDistinctFrom,
+                _ => unreachable!(),
+            };
+            CTypedExpr {
+                type_: check_enum_literals(
+                    &compiler,
+                    loc,
+                    args,
+                    resolve_global_atom(compiler.clone(), "bool")?,
+                )?,
+                expr: combine_binary_sqlexpr(&casted[0], &casted[1], constructor)?,
+            }
+        }
+        sqlast::Expr::JsonAccess {
+            left,
+            operator: operator @ (sqlast::JsonOperator::AtArrow | sqlast::JsonOperator::ArrowAt),
+            right,
+        } => {
+            // The parser treats `@>` and `<@` as JSON operators, but we only support them as
+            // list containment checks.
+            //
+            let operator = operator.clone();
+            check_engine_operator(&compiler, loc, &operator.to_string())?;
+            let (_, casted) = coerce_all(
+                &compiler,
+                &CoerceOp::ListContains,
+                vec![c_sqlarg(left)?, c_sqlarg(right)?],
+                "list contains",
+            )?;
+
+            CTypedExpr {
+                type_: resolve_global_atom(compiler.clone(), "bool")?,
+                expr: combine_binary_sqlexpr(&casted[0], &casted[1], move |left, right| {
+                    sqlast::Expr::JsonAccess {
+                        left,
+                        operator: operator.clone(),
+                        right,
                     }
                 })?,
             }
@@ -2007,11 +3165,10 @@ pub fn compile_sqlexpr(
                     cexpr.type_.clone()
                 }
                 Not => resolve_global_atom(compiler.clone(), "bool")?,
-                _ => {
-                    return Err(CompileError::unimplemented(
-                        loc.clone(),
-                        format!("Unary operator: {}", op).as_str(),
-                    ));
+                PGBitwiseNot | PGPostfixFactorial | PGPrefixFactorial | PGSquareRoot
+                | PGCubeRoot | PGAbs => {
+                    check_engine_operator(&compiler, loc, &op.to_string())?;
+                    unary_coerce(&compiler, loc, &op, &cexpr.type_)?
                 }
             };
             CTypedExpr {
@@ -2057,6 +3214,22 @@ pub fn compile_sqlexpr(
                 c_cond.type_.unify(&condition_type)?;
             }
 
+            // Each arm of a CASE with an operand compares the operand with the arm's condition.
+            //
+            let compared_arms = match (operand, &c_operand) {
+                (Some(operand), Some(c_operand)) => Some(
+                    std::iter::once((operand.as_ref().clone(), c_operand.type_.clone()))
+                        .chain(
+                            conditions
+                                .iter()
+                                .zip(c_conditions.iter())
+                                .map(|(cond, c_cond)| (cond.clone(), c_cond.type_.clone())),
+                        )
+                        .collect::<Vec<_>>(),
+                ),
+                _ => None,
+            };
+
             let mut c_results = results
                 .iter()
                 .map(|c| c_sqlarg(&c))
@@ -2069,10 +3242,14 @@ pub fn compile_sqlexpr(
 
             let (result_type, mut c_results) = coerce_all(
                 &compiler,
-                &sqlast::BinaryOperator::Eq,
+                &CoerceOp::Binary(sqlast::BinaryOperator::Eq),
                 c_results,
                 "case result",
             )?;
+            let result_type = match compared_arms {
+                Some(arms) => check_enum_literals(&compiler, loc, arms, result_type)?,
+                None => result_type,
+            };
 
             let c_else_result = match else_result {
                 Some(_) => Some(c_results.pop().unwrap()),
@@ -2144,52 +3321,16 @@ pub fn compile_sqlexpr(
             let over = over.compile_sql(&compiler, &schema, &scope, loc)?;
 
             let func_name = name.to_path(file.clone());
-            let func = compile_reference(compiler.clone(), schema.clone(), &func_name)?;
-            let fn_type = match func
-                .type_
-                .must()
-                .context(RuntimeSnafu { loc: loc.clone() })?
-                .read()?
-                .clone()
-            {
-                MType::Fn(f) => f,
-                _ => {
-                    return Err(CompileError::wrong_type(
-                        &MType::Fn(Located::new(
-                            MFnType {
-                                args: Vec::new(),
-                                ret: MType::new_unknown("ret"),
-                            },
-                            loc.clone(),
-                        )),
-                        &*func
-                            .type_
-                            .must()
-                            .context(RuntimeSnafu { loc: loc.clone() })?
-                            .read()?,
-                    ))
-                }
-            };
-            let mut compiled_args: BTreeMap<Ident, CTypedNameAndExpr> = BTreeMap::new();
-            let mut pos: usize = 0;
+            let candidates = compile_overloads(compiler.clone(), schema.clone(), &func_name)?;
+            let allow_aggregates = scope.read()?.allow_aggregates();
+
+            let mut call_args = Vec::new();
             for arg in args {
                 let (name, expr) = match arg {
                     sqlast::FunctionArg::Named { name, arg } => {
-                        (Ident::with_location(loc.clone(), name.get()), arg)
-                    }
-                    sqlast::FunctionArg::Unnamed(arg) => {
-                        if pos >= fn_type.args.len() {
-                            return Err(CompileError::no_such_entry(vec![Ident::with_location(
-                                loc.clone(),
-                                format!("argument {}", pos),
-                            )]));
-                        }
-                        pos += 1;
-                        (
-                            Ident::with_location(loc.clone(), fn_type.args[pos - 1].name.clone()),
-                            arg,
-                        )
+                        (Some(Ident::with_location(loc.clone(), name.get())), arg)
                     }
+                    sqlast::FunctionArg::Unnamed(arg) => (None, arg),
                 };
 
                 let expr = match expr {
@@ -2217,44 +3358,38 @@ pub fn compile_sqlexpr(
                     }
                 };
 
-                if compiled_args.get(&name).is_some() {
-                    return Err(CompileError::duplicate_entry(vec![name]));
-                }
-
                 let compiled_arg =
                     compile_sqlexpr(compiler.clone(), schema.clone(), scope.clone(), loc, &expr)?;
-                compiled_args.insert(
-                    name.get().clone(),
-                    CTypedNameAndExpr {
-                        name: name.get().clone(),
-                        type_: compiled_arg.type_,
-                        expr: compiled_arg.expr,
-                    },
-                );
-            }
-
-            let mut arg_exprs = Vec::new();
-            for arg in &fn_type.args {
-                if let Some(compiled_arg) = compiled_args.get_mut(&arg.name) {
-                    arg.type_.unify(&compiled_arg.type_)?;
-                    arg_exprs.push(compiled_arg.clone());
-                } else if arg.nullable {
-                    // If the argument is missing and nullable, then set it to NULL
-                    // as a default value. Eventually we may want to generalize this
-                    // so that functions can declare other kinds of default values too.
-                    arg_exprs.push(CTypedNameAndExpr {
-                        name: arg.name.clone(),
-                        type_: NULL.type_.clone(),
-                        expr: NULL.expr.clone(),
-                    });
-                } else {
-                    return Err(CompileError::missing_arg(vec![Ident::without_location(
-                        arg.name.clone(),
-                    )]));
-                }
+                call_args.push((name, compiled_arg));
             }
 
-            let type_ = fn_type.ret.clone();
+            // If the function is overloaded, then we can only bind the arguments once their types
+            // are known (and we can pick an overload).
+            let (binding, type_) = if candidates.len() == 1 {
+                let func = candidates.into_iter().next().unwrap();
+                let fn_type = fn_type_of(&func, loc)?;
+                let args = bind_fn_args(compiler.clone(), &func, &fn_type, &call_args, loc)?;
+                let type_ = fn_type.ret.clone();
+                (
+                    mkcref(FnCallBinding {
+                        func,
+                        fn_type,
+                        args,
+                    }),
+                    type_,
+                )
+            } else {
+                let type_ = MType::new_unknown("ret");
+                let binding = resolve_overload(
+                    compiler.clone(),
+                    loc,
+                    &func_name,
+                    candidates,
+                    call_args,
+                    type_.clone(),
+                )?;
+                (binding, type_)
+            };
 
             let expr = compiler.async_cref({
                 let compiler = compiler.clone();
@@ -2263,6 +3398,12 @@ pub fn compile_sqlexpr(
                 let name = name.clone();
                 let type_ = type_.clone();
                 async move {
+                    let FnCallBinding {
+                        func,
+                        fn_type,
+                        args: arg_exprs,
+                    } = binding.await?.read()?.clone();
+
                     let arg_exprs = arg_exprs
                         .into_iter()
                         .map(move |cte| {
@@ -2332,6 +3473,7 @@ pub fn compile_sqlexpr(
                             FnBody::SQLBuiltin => (FnKind::SQLBuiltin, None),
                             FnBody::Expr(expr) => (FnKind::Expr, Some(expr.clone())),
                         },
+                        Expr::ContextRef(_) => (FnKind::Argument, None),
                         _ => {
                             return Err(CompileError::internal(
                                 loc.clone(),
@@ -2340,6 +3482,38 @@ pub fn compile_sqlexpr(
                         }
                     };
 
+                    // Aggregate functions are always inlined into the surrounding query, since
+                    // they cannot be evaluated one row at a time.
+                    //
+                    if matches!(
+                        func_expr.as_ref(),
+                        Expr::Fn(FnExpr {
+                            aggregate: true,
+                            ..
+                        })
+                    ) {
+                        if !allow_aggregates {
+                            return Err(CompileError::aggregate_context(
+                                loc.clone(),
+                                func_name.clone(),
+                            ));
+                        }
+                        if !matches!(fn_kind, FnKind::SQLBuiltin) {
+                            if !compiler.allow_inlining()? {
+                                return Err(CompileError::unimplemented(
+                                    loc.clone(),
+                                    "aggregate functions without inlining",
+                                ));
+                            }
+                            if over.body.is_some() {
+                                return Err(CompileError::unimplemented(
+                                    loc.clone(),
+                                    "window clauses on user-defined aggregate functions",
+                                ));
+                            }
+                        }
+                    }
+
                     // Function calls against native functions that do not reference any unbound
                     // SQL names in their arguments can be lifted out of the SQL body, which is
                     // important because we don't yet support running native functions within SQL.
@@ -2392,10 +3566,53 @@ pub fn compile_sqlexpr(
                                 // the SQL of the function body.  This should result in a version
                                 // of the body with all SQL arguments fully inlined.
                                 //
-                                let fn_body = inline_params(fn_body).await?;
+                                let fn_body = inline_params(fn_body, &loc).await?;
 
                                 Ok(mkcref(fn_body.as_ref().clone()))
                             }
+                            // If the function is an argument of the enclosing function, then its
+                            // body is only known once that function is called (and inlined). Until
+                            // then, the call is a parameter of the SQL, which inline_params inlines
+                            // once the function is known. The names referenced by its arguments are
+                            // tracked as if it were already inlined.
+                            //
+                            (FnKind::Argument, _) if compiler.allow_inlining()? => {
+                                let call = TypedExpr {
+                                    type_: type_.clone(),
+                                    expr: Arc::new(Expr::FnCall(FnCallExpr {
+                                        func: Arc::new(TypedExpr {
+                                            type_: mkcref(MType::Fn(fn_type.clone())),
+                                            expr: func.expr.clone(),
+                                        }),
+                                        args: args
+                                            .iter()
+                                            .map(TypedNameAndExpr::to_typed_expr)
+                                            .collect(),
+                                        ctx_folder: schema.read()?.folder.clone(),
+                                    })),
+                                };
+                                let (_, placeholder) =
+                                    intern_nonsql_placeholder(compiler.clone(), "call", &call)?;
+                                let mut names = placeholder.names.clone();
+                                for arg in &args {
+                                    if let Expr::SQL(sql) = arg.expr.as_ref() {
+                                        names.unbound.extend(sql.names.unbound.clone());
+                                    }
+                                }
+                                Ok(mkcref(Expr::SQL(Arc::new(SQL {
+                                    names,
+                                    body: placeholder.body.clone(),
+                                }))))
+                            }
+                            // Without inlining, a call to a function passed as an argument
+                            // would have to be run within the SQL, but the engine can't call
+                            // function values (it rejects them as parameters).
+                            //
+                            (FnKind::Argument, _) => Err(CompileError::inlining_required(
+                                loc.clone(),
+                                format!("Calling the function argument {}", func_name.pretty())
+                                    .as_str(),
+                            )),
                             // Otherwise, create a SQL function call.
                             //
                             _ => {
@@ -2475,6 +3692,7 @@ pub fn compile_sqlexpr(
                                 name: format!("f{}", i + 1).into(),
                                 type_: t.clone(),
                                 nullable: true,
+                                loc: loc.clone(),
                             })
                         })
                         .collect::<Result<Vec<_>>>()?,
@@ -2600,6 +3818,56 @@ pub fn compile_sqlexpr(
 
             CTypedExpr { type_, expr }
         }
+        sqlast::Expr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let exprs = std::iter::once(expr.as_ref())
+                .chain(list.iter())
+                .collect::<Vec<_>>();
+            let c_exprs = exprs
+                .iter()
+                .map(|e| c_sqlarg(e))
+                .collect::<Result<Vec<_>>>()?;
+            let args = exprs
+                .iter()
+                .zip(c_exprs.iter())
+                .map(|(e, c)| ((*e).clone(), c.type_.clone()))
+                .collect();
+
+            let (_, casted) = coerce_all(
+                &compiler,
+                &CoerceOp::Binary(sqlast::BinaryOperator::Eq),
+                c_exprs,
+                "in list",
+            )?;
+            let combined = combine_crefs(casted.iter().map(|s| s.sql.clone()).collect())?;
+            let negated = *negated;
+
+            CTypedExpr {
+                type_: check_enum_literals(
+                    &compiler,
+                    loc,
+                    args,
+                    resolve_global_atom(compiler.clone(), "bool")?,
+                )?,
+                expr: compiler.async_cref(async move {
+                    let mut names = CSQLNames::new();
+                    let mut list = combine_sql_exprs(combined.await?.read()?.iter(), &mut names)?;
+                    let expr = list.remove(0);
+
+                    Ok(mkcref(Expr::SQL(Arc::new(SQL {
+                        names,
+                        body: SQLBody::Expr(sqlast::Expr::InList {
+                            expr: Box::new(expr),
+                            list,
+                            negated,
+                        }),
+                    }))))
+                })?,
+            }
+        }
         sqlast::Expr::InSubquery {
             expr,
             subquery,
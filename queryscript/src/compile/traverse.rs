@@ -825,8 +825,15 @@ impl<V: Visitor<schema::CRef<schema::MType>> + Sync> Visit<V, schema::CRef<schem
                     },
                 }))
             }
-            Expr::Fn(FnExpr { inner_schema, body }) => Expr::Fn(FnExpr {
+            Expr::Fn(FnExpr {
+                inner_schema,
+                body,
+                args,
+                aggregate,
+            }) => Expr::Fn(FnExpr {
                 inner_schema: inner_schema.clone(),
+                args: args.clone(),
+                aggregate: *aggregate,
                 body: match body {
                     FnBody::SQLBuiltin => FnBody::SQLBuiltin,
                     FnBody::Expr(expr) => FnBody::Expr(Arc::new(expr.visit(visitor).await?)),
@@ -86,7 +86,7 @@ fn validate_inferred_type(type_: &Type) -> Result<()> {
         Type::Atom(..) => {}
         Type::Fn(..) => {}
         Type::List(inner) => validate_inferred_type(inner)?,
-        Type::Record(fields) => {
+        Type::Record(fields) | Type::Union(fields) => {
             let mut seen = std::collections::HashSet::new();
             for field in fields {
                 if seen.contains(&field.name) {
@@ -162,6 +162,12 @@ pub fn compile_unsafe_expr(
             let transformed = SQLBody::Expr(expr.visit_sql(&name_collector));
             (transformed.clone(), transformed)
         }
+        ast::ExprBody::ModelQuery(_) => {
+            return Err(CompileError::internal(
+                loc.clone(),
+                "model queries must be expanded before they are compiled",
+            ))
+        }
     };
 
     let names = name_collector.names.into_inner();
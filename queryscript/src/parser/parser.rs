@@ -9,8 +9,9 @@ use sqlparser::{
     ast as sqlast,
     dialect::{keywords::Keyword, GenericDialect},
     parser,
-    tokenizer::{TokenWithLocation, Tokenizer},
+    tokenizer::{TokenWithLocation, Tokenizer, Whitespace},
 };
+use std::collections::BTreeMap;
 
 pub use sqlparser::tokenizer::Location;
 pub use sqlparser::tokenizer::Token;
@@ -32,17 +33,69 @@ type ParseResult<T> = MultiResult<T, ParserError>;
 pub struct Parser<'a> {
     file: String,
     sqlparser: parser::Parser<'a>,
+
+    // The text of each `---` doc comment, keyed by the line it's on.
+    doc_comments: BTreeMap<u64, String>,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(file: &str, tokens: Vec<TokenWithLocation>, eof: Location) -> Parser<'a> {
         let dialect = &GenericDialect {};
+
+        // The SQL parser skips over comments, so we pull out the doc comments beforehand.
+        //
+        let mut doc_comments = BTreeMap::new();
+        let mut code_line = None;
+        for token in tokens.iter() {
+            match &token.token {
+                Token::Whitespace(Whitespace::SingleLineComment { comment, prefix }) => {
+                    // Lines of dashes (e.g. separators) are ordinary comments, not empty doc
+                    // comments. A doc comment must also be the first token on its line, so that a
+                    // trailing comment (e.g. `id int, --- the id`) isn't attached to whatever is
+                    // declared on the next line.
+                    //
+                    let text = comment.trim_start_matches('-').trim();
+                    if prefix == "--"
+                        && comment.starts_with('-')
+                        && !text.is_empty()
+                        && code_line != Some(token.location.line)
+                    {
+                        doc_comments.insert(token.location.line, text.to_string());
+                    }
+                }
+                Token::Whitespace(_) => {}
+                _ => code_line = Some(token.location.line),
+            }
+        }
+
         Parser {
             file: file.to_string(),
             sqlparser: parser::Parser::new_with_locations(tokens, eof, dialect),
+            doc_comments,
         }
     }
 
+    // Returns the doc comment on the lines immediately preceding `start`, if there is one. A doc
+    // comment may span several lines, which are joined with newlines.
+    //
+    pub fn doc_comment(&self, start: &Location) -> Option<String> {
+        let mut lines = Vec::new();
+        let mut line = start.line;
+        while line > 1 {
+            line -= 1;
+            match self.doc_comments.get(&line) {
+                Some(text) => lines.push(text.as_str()),
+                None => break,
+            }
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+        lines.reverse();
+        Some(lines.join("\n"))
+    }
+
     pub fn next_token(&mut self) -> TokenWithLocation {
         self.sqlparser.next_token()
     }
@@ -209,6 +262,7 @@ impl<'a> Parser<'a> {
         let start = self.peek_start_location();
         let mut result = ParseResult::<Stmt>::new(Stmt {
             export: false,
+            doc: None,
             body: StmtBody::Noop,
             start: start.clone(),
             end: start.clone(),
@@ -219,13 +273,24 @@ impl<'a> Parser<'a> {
 
         let export = self.consume_keyword("export");
         let body = if self.consume_keyword("fn") {
-            self.parse_fn()
+            self.parse_fn(false /* aggregate */)
+        } else if self.consume_keyword("agg") {
+            self.expect_keyword("fn")
+                .and_then(|_| self.parse_fn(true /* aggregate */))
         } else if self.consume_keyword("extern") {
             self.parse_extern()
         } else if self.consume_keyword("let") {
             self.parse_let()
         } else if self.consume_keyword("type") {
             self.parse_typedef()
+        } else if self.peek_keyword("test") && self.peek_test_name() {
+            self.next_token();
+            self.parse_test()
+        } else if self.consume_keyword("assert") {
+            self.parse_assert()
+        } else if self.peek_keyword("model") && self.peek_model() {
+            self.next_token();
+            self.parse_model()
         } else if self.consume_keyword("import") || export {
             self.parse_import()
         } else {
@@ -237,6 +302,7 @@ impl<'a> Parser<'a> {
                 let end = self.prev_end_location();
                 result.set_result(Stmt {
                     export,
+                    doc: self.doc_comment(&start),
                     body,
                     start,
                     end,
@@ -248,9 +314,13 @@ impl<'a> Parser<'a> {
                 //
                 while !self.peek_keyword("export")
                     && !self.peek_keyword("fn")
+                    && !self.peek_keyword("agg")
                     && !self.peek_keyword("extern")
                     && !self.peek_keyword("let")
                     && !self.peek_keyword("type")
+                    && !self.peek_keyword("test")
+                    && !self.peek_keyword("assert")
+                    && !self.peek_keyword("model")
                     && !self.peek_keyword("import")
                     && !self.peek_keyword("select")
                     && !self.peek_keyword("with")
@@ -267,6 +337,7 @@ impl<'a> Parser<'a> {
 
                 result.set_result(Stmt {
                     export: false,
+                    doc: None,
                     body: StmtBody::Unparsed,
                     start,
                     end,
@@ -354,14 +425,30 @@ impl<'a> Parser<'a> {
 
     pub fn parse_simple_import(&mut self) -> Result<StmtBody> {
         let path = self.parse_path(AUTOCOMPLETE_SCHEMA)?;
+        let alias = self.parse_import_alias()?;
         self.expect_eos()?;
         Ok(StmtBody::Import {
             path,
             list: ImportList::None,
             args: None,
+            alias,
         })
     }
 
+    pub fn parse_import_alias(&mut self) -> Result<Option<Located<Ident>>> {
+        Ok(if self.consume_keyword("as") {
+            Some(self.parse_ident()?)
+        } else {
+            None
+        })
+    }
+
+    pub fn parse_import_item(&mut self) -> Result<ImportItem> {
+        let path = self.parse_path(AUTOCOMPLETE_VARIABLE)?;
+        let alias = self.parse_import_alias()?;
+        Ok(ImportItem { path, alias })
+    }
+
     pub fn parse_import(&mut self) -> Result<StmtBody> {
         if let Some(stmt) = self.maybe_parse(Parser::parse_simple_import) {
             return Ok(stmt);
@@ -370,12 +457,11 @@ impl<'a> Parser<'a> {
         let list = if self.consume_token(&Token::Mul) {
             ImportList::Star
         } else {
-            ImportList::Items(
-                self.parse_idents()?
-                    .iter()
-                    .map(|x| vec![x.clone()])
-                    .collect(),
-            )
+            let mut items = vec![self.parse_import_item()?];
+            while self.consume_token(&Token::Comma) {
+                items.push(self.parse_import_item()?);
+            }
+            ImportList::Items(items)
         };
         self.expect_keyword("from")?;
 
@@ -408,20 +494,22 @@ impl<'a> Parser<'a> {
 
         self.expect_eos()?;
 
-        Ok(StmtBody::Import { path, list, args })
+        Ok(StmtBody::Import {
+            path,
+            list,
+            args,
+            alias: None,
+        })
     }
 
-    pub fn parse_fn(&mut self) -> Result<StmtBody> {
-        // Assume the leading "fn" has already been consumed
+    pub fn parse_fn(&mut self, aggregate: bool) -> Result<StmtBody> {
+        // Assume the leading "fn" (or "agg fn") has already been consumed
         //
         let name = self.parse_ident()?;
-        let generics = if self.consume_token(&Token::Lt) {
-            let list = self.parse_idents()?;
-            self.expect_token(&Token::Gt)?;
-
-            list
+        let (generics, bounds) = if self.consume_token(&Token::Lt) {
+            self.parse_generic_bounds()?
         } else {
-            Vec::new()
+            (Vec::new(), BTreeMap::new())
         };
 
         self.expect_token(&Token::LParen)?;
@@ -433,8 +521,19 @@ impl<'a> Parser<'a> {
             loop {
                 let name = self.parse_ident()?;
                 let type_ = self.parse_type()?;
+                let nullable = !self.parse_not_null()?;
+                let default = if self.consume_token(&Token::Eq) {
+                    Some(self.parse_expr()?)
+                } else {
+                    None
+                };
 
-                args.push(FnArg { name, type_ });
+                args.push(FnArg {
+                    name,
+                    type_,
+                    nullable,
+                    default,
+                });
 
                 self.autocomplete_tokens(&[Token::Comma, Token::RParen]);
                 match self.next_token().token {
@@ -462,6 +561,10 @@ impl<'a> Parser<'a> {
                 FnBody::Native
             } else if self.consume_keyword("sql") {
                 FnBody::SQL
+            } else if aggregate {
+                // Aggregates are usually one-liners, e.g. `agg fn total(v double) = sum(v)`, so
+                // their bodies may also be written as `= expr`.
+                FnBody::Expr(self.parse_expr()?)
             } else {
                 return unexpected_token!(
                     self.file.clone(),
@@ -484,9 +587,45 @@ impl<'a> Parser<'a> {
             args,
             ret,
             body,
+            aggregate,
+            bounds,
         })
     }
 
+    // Parses the generic arguments of a function (after the leading '<'), each of which may have
+    // a bound, e.g. `<R: {id bigint, ..}, S>`.
+    //
+    fn parse_generic_bounds(&mut self) -> Result<(Vec<Located<Ident>>, BTreeMap<Ident, Type>)> {
+        let mut generics = Vec::new();
+        let mut bounds = BTreeMap::new();
+        if self.consume_token(&Token::Gt) {
+            return Ok((generics, bounds));
+        }
+
+        loop {
+            let generic = self.parse_ident()?;
+            if self.consume_token(&Token::Colon) {
+                bounds.insert(generic.get().clone(), self.parse_type()?);
+            }
+            generics.push(generic);
+
+            self.autocomplete_tokens(&[Token::Comma, Token::Gt]);
+            match self.next_token().token {
+                Token::Comma => {}
+                Token::Gt => break,
+                _ => {
+                    return unexpected_token!(
+                        self.file.clone(),
+                        self.peek_token(),
+                        "Expected: ',' | '>'"
+                    );
+                }
+            }
+        }
+
+        Ok((generics, bounds))
+    }
+
     pub fn parse_let(&mut self) -> Result<StmtBody> {
         // Assume the leading "let" or "export" keywords have already been consumed
         //
@@ -524,16 +663,225 @@ impl<'a> Parser<'a> {
         Ok(StmtBody::Expr(expr))
     }
 
+    // A test's name is a quoted string, which lets us tell `test "name" { ... }` apart from an
+    // expression that references something called `test`.
+    //
+    fn peek_test_name(&self) -> bool {
+        match self.sqlparser.peek_nth_token(1).token {
+            Token::SingleQuotedString(_) => true,
+            Token::Word(w) => w.quote_style == Some('"'),
+            _ => false,
+        }
+    }
+
+    // Returns whether the token `n` tokens ahead is a name followed by a `{`, like in
+    // `model orders { ... }`.
+    //
+    fn peek_block(&self, n: usize) -> bool {
+        matches!(self.sqlparser.peek_nth_token(n).token, Token::Word(_))
+            && self.sqlparser.peek_nth_token(n + 1).token == Token::LBrace
+    }
+
+    // Whether the next tokens start a model, i.e. `model <name> {` or `model <name> from`.
+    //
+    fn peek_model(&self) -> bool {
+        if !matches!(self.sqlparser.peek_nth_token(1).token, Token::Word(_)) {
+            return false;
+        }
+        match self.sqlparser.peek_nth_token(2).token {
+            Token::LBrace => true,
+            Token::Word(w) => w.value.to_lowercase() == "from",
+            _ => false,
+        }
+    }
+
+    pub fn parse_test(&mut self) -> Result<StmtBody> {
+        // Assume the leading "test" has already been consumed
+        //
+        let start = self.peek_start_location();
+        let name = match self.next_token().token {
+            Token::SingleQuotedString(s) => s,
+            Token::Word(w) if w.quote_style == Some('"') => w.value,
+            _ => {
+                return unexpected_token!(
+                    self.file.clone(),
+                    self.peek_token(),
+                    "Expected test name"
+                );
+            }
+        };
+        let name = Located::new(
+            name,
+            SourceLocation::Range(
+                self.file.clone(),
+                Range {
+                    start,
+                    end: self.prev_end_location(),
+                },
+            ),
+        );
+
+        self.expect_token(&Token::LBrace)?;
+        let body = self.parse_expr()?;
+        self.expect_token(&Token::RBrace)?;
+
+        self.expect_keyword("expect")?;
+        self.expect_keyword("empty")?;
+        self.expect_eos()?;
+
+        Ok(StmtBody::Test { name, body })
+    }
+
+    pub fn parse_assert(&mut self) -> Result<StmtBody> {
+        // Assume the leading "assert" has already been consumed
+        //
+        let expr = self.parse_expr()?;
+        self.expect_eos()?;
+
+        Ok(StmtBody::Assert(expr))
+    }
+
+    pub fn parse_model(&mut self) -> Result<StmtBody> {
+        // Assume the leading "model" has already been consumed
+        //
+        let name = self.parse_ident()?;
+        let relation = if self.consume_keyword("from") {
+            let start = self.peek_start_location();
+            Some(
+                self.sqlparser
+                    .parse_table_factor()
+                    .context(self.range_context(&start))?,
+            )
+        } else {
+            None
+        };
+        let mut model = Model {
+            name,
+            relation,
+            joins: Vec::new(),
+            measures: Vec::new(),
+            dimensions: Vec::new(),
+        };
+
+        self.expect_token(&Token::LBrace)?;
+        loop {
+            self.autocomplete_tokens(&[Token::RBrace]);
+            if self.consume_token(&Token::RBrace) {
+                break;
+            }
+
+            if self.consume_keyword("measure") {
+                model.measures.push(self.parse_model_field()?);
+            } else if self.consume_keyword("dimension") {
+                model.dimensions.push(self.parse_model_field()?);
+            } else if self.consume_keyword("join") {
+                let joined = self.parse_ident()?;
+                self.expect_keyword("on")?;
+                let start = self.peek_start_location();
+                let on = self
+                    .sqlparser
+                    .parse_expr()
+                    .context(self.range_context(&start))?;
+                model.joins.push(ModelJoin { model: joined, on });
+            } else {
+                return unexpected_token!(
+                    self.file.clone(),
+                    self.peek_token(),
+                    "Expected: measure | dimension | join"
+                );
+            }
+
+            self.autocomplete_tokens(&[Token::SemiColon, Token::RBrace]);
+            if !self.consume_token(&Token::SemiColon) {
+                self.expect_token(&Token::RBrace)?;
+                break;
+            }
+        }
+
+        Ok(StmtBody::Model(model))
+    }
+
+    fn parse_model_field(&mut self) -> Result<ModelField> {
+        let name = self.parse_ident()?;
+        self.expect_token(&Token::Eq)?;
+        let start = self.peek_start_location();
+        let expr = self
+            .sqlparser
+            .parse_expr()
+            .context(self.range_context(&start))?;
+
+        Ok(ModelField { name, expr })
+    }
+
+    pub fn parse_model_query(&mut self) -> Result<ModelQuery> {
+        // Assume the leading "query" has already been consumed
+        //
+        let model = self.parse_ident()?;
+        let mut query = ModelQuery {
+            model,
+            measures: Vec::new(),
+            dimensions: Vec::new(),
+        };
+
+        self.expect_token(&Token::LBrace)?;
+        loop {
+            self.autocomplete_tokens(&[Token::RBrace]);
+            if self.consume_token(&Token::RBrace) {
+                break;
+            }
+
+            let paths = if self.consume_keyword("measures") {
+                &mut query.measures
+            } else if self.consume_keyword("dimensions") {
+                &mut query.dimensions
+            } else {
+                return unexpected_token!(
+                    self.file.clone(),
+                    self.peek_token(),
+                    "Expected: measures | dimensions"
+                );
+            };
+            paths.push(self.parse_path(AUTOCOMPLETE_VARIABLE)?);
+            while self.consume_token(&Token::Comma) {
+                paths.push(self.parse_path(AUTOCOMPLETE_VARIABLE)?);
+            }
+
+            self.autocomplete_tokens(&[Token::SemiColon, Token::RBrace]);
+            if !self.consume_token(&Token::SemiColon) {
+                self.expect_token(&Token::RBrace)?;
+                break;
+            }
+        }
+
+        Ok(query)
+    }
+
     pub fn parse_typedef(&mut self) -> Result<StmtBody> {
         // Assume the leading keywords have already been consumed
         //
         let name = self.parse_ident()?;
+        let generics = if self.consume_token(&Token::Lt) {
+            let list = self.parse_idents()?;
+            self.expect_token(&Token::Gt)?;
+
+            list
+        } else {
+            Vec::new()
+        };
+
+        // The definition may optionally be preceded by '=', e.g. `type Paged<R> = { ... }`
+        self.consume_token(&Token::Eq);
+
         let def = self.parse_type()?;
         match def.body {
-            TypeBody::Struct(_) => {}
+            TypeBody::Struct(_) | TypeBody::Union(_) => {}
             _ => self.expect_eos()?,
         }
-        Ok(StmtBody::TypeDef(NameAndType { name, def }))
+        Ok(StmtBody::TypeDef {
+            name,
+            generics,
+            def,
+        })
     }
 
     pub fn parse_type(&mut self) -> Result<Type> {
@@ -545,6 +893,72 @@ impl<'a> Parser<'a> {
             TypeBody::List(Box::new(inner))
         } else if self.peek_token().token == Token::LBrace {
             self.parse_struct()?
+        } else if self.consume_keyword("union") {
+            self.parse_union()?
+        } else if self.consume_keyword("enum") {
+            // An enum type lists its members as string literals, e.g. `enum ('a', 'b')`
+            self.expect_token(&Token::LParen)?;
+            let mut members = Vec::new();
+            loop {
+                members.push(
+                    self.sqlparser
+                        .parse_literal_string()
+                        .context(self.token_context())?,
+                );
+                if !self.consume_token(&Token::Comma) {
+                    break;
+                }
+            }
+            self.expect_token(&Token::RParen)?;
+            TypeBody::Enum(members)
+        } else if self.consume_keyword("fn") {
+            // A function type is written as `fn(A, B) -> R`. Its arguments are unnamed.
+            self.expect_token(&Token::LParen)?;
+            let mut args = Vec::new();
+            if !self.consume_token(&Token::RParen) {
+                loop {
+                    args.push(self.parse_type()?);
+                    self.autocomplete_tokens(&[Token::Comma, Token::RParen]);
+                    match self.next_token().token {
+                        Token::Comma => {}
+                        Token::RParen => break,
+                        _ => {
+                            return unexpected_token!(
+                                self.file.clone(),
+                                self.peek_token(),
+                                "Expected: ',' | ')'"
+                            );
+                        }
+                    }
+                }
+            }
+            self.expect_token(&Token::Arrow)?;
+            let ret = self.parse_type()?;
+            TypeBody::Fn {
+                args,
+                ret: Box::new(ret),
+            }
+        } else if self.consume_keyword("dictionary") {
+            // A dictionary type is written as `dictionary<V>` or `dictionary<K, V>`, where `K` is
+            // the (integer) key type and `V` is the type of the values.
+            self.expect_token(&Token::Lt)?;
+            let first = self.parse_type()?;
+            let second = if self.consume_token(&Token::Comma) {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            self.expect_token(&Token::Gt)?;
+            match second {
+                Some(value) => TypeBody::Dictionary {
+                    key: Some(Box::new(first)),
+                    value: Box::new(value),
+                },
+                None => TypeBody::Dictionary {
+                    key: None,
+                    value: Box::new(first),
+                },
+            }
         } else {
             let type_name = self.parse_path(AUTOCOMPLETE_TYPE)?;
             if self.consume_token(&Token::Lt) {
@@ -565,13 +979,64 @@ impl<'a> Parser<'a> {
                     }
                 }
                 TypeBody::Generic(type_name, args)
+            } else if self.consume_token(&Token::LParen) {
+                // Builtin types may take arguments, which are numbers, identifiers, or strings,
+                // e.g. `decimal(10, 2)` or `timestamp(ms, 'UTC')`.
+                let mut args = Vec::new();
+                loop {
+                    let start = self.peek_start_location();
+                    let arg = match self.next_token().token {
+                        Token::Number(n, _) => n,
+                        Token::SingleQuotedString(s) => s,
+                        Token::Word(w) => w.value,
+                        _ => {
+                            return unexpected_token!(
+                                self.file.clone(),
+                                self.peek_token(),
+                                "Expected: number | identifier | string"
+                            );
+                        }
+                    };
+                    args.push(Located::new(
+                        arg,
+                        SourceLocation::Range(
+                            self.file.clone(),
+                            Range {
+                                start,
+                                end: self.prev_end_location(),
+                            },
+                        ),
+                    ));
+
+                    self.autocomplete_tokens(&[Token::Comma, Token::RParen]);
+                    match self.next_token().token {
+                        Token::Comma => {}
+                        Token::RParen => break,
+                        _ => {
+                            return unexpected_token!(
+                                self.file.clone(),
+                                self.peek_token(),
+                                "Expected: ',' | ')'"
+                            );
+                        }
+                    }
+                }
+                TypeBody::Parameterized(type_name, args)
             } else {
                 TypeBody::Reference(type_name)
             }
         };
 
         if self.consume_keyword("exclude") {
-            let excluded = self.parse_idents()?;
+            // The list of excluded fields may optionally be wrapped in parentheses, e.g.
+            // `T EXCLUDE (a, b)`.
+            let excluded = if self.consume_token(&Token::LParen) {
+                let excluded = self.parse_idents()?;
+                self.expect_token(&Token::RParen)?;
+                excluded
+            } else {
+                self.parse_idents()?
+            };
             let end = self.prev_end_location();
             body = TypeBody::Exclude {
                 inner: Box::new(Type {
@@ -612,18 +1077,29 @@ impl<'a> Parser<'a> {
                     self.next_token();
                 }
                 Token::Period => {
-                    for _ in 0..3 {
+                    // `...T` includes the fields of T, and `..` allows any other fields.
+                    let mut periods = 0;
+                    while periods < 3 {
                         self.autocomplete_tokens(&[Token::Period]);
                         if !matches!(self.peek_token().token, Token::Period) {
+                            break;
+                        }
+                        self.next_token();
+                        periods += 1;
+                    }
+                    match periods {
+                        2 => struct_.push(StructEntry::Rest),
+                        3 => {
+                            struct_.push(StructEntry::Include(self.parse_path(AUTOCOMPLETE_TYPE)?))
+                        }
+                        _ => {
                             return unexpected_token!(
                                 self.file.clone(),
                                 self.peek_token(),
-                                "Three periods"
+                                "Expected: '..' | '...'"
                             );
                         }
-                        self.next_token();
                     }
-                    struct_.push(StructEntry::Include(self.parse_path(AUTOCOMPLETE_TYPE)?));
                     needs_comma = true;
                 }
                 _ => {
@@ -634,9 +1110,16 @@ impl<'a> Parser<'a> {
                             "Expected a comma before the next type declaration"
                         );
                     }
+                    let doc = self.doc_comment(&self.peek_start_location());
                     let name = self.parse_ident()?;
                     let def = self.parse_type()?;
-                    struct_.push(StructEntry::NameAndType(NameAndType { name, def }));
+                    let nullable = !self.parse_not_null()?;
+                    struct_.push(StructEntry::NameAndType(NameAndType {
+                        name,
+                        def,
+                        nullable,
+                        doc,
+                    }));
                     needs_comma = true;
                 }
             }
@@ -644,6 +1127,50 @@ impl<'a> Parser<'a> {
         Ok(TypeBody::Struct(struct_))
     }
 
+    // Parses an optional `NOT NULL` after the type of a field or argument.
+    pub fn parse_not_null(&mut self) -> Result<bool> {
+        if self.consume_keyword("not") {
+            self.expect_keyword("null")?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub fn parse_union(&mut self) -> Result<TypeBody> {
+        self.expect_token(&Token::LBrace)?;
+        let mut variants = Vec::new();
+        loop {
+            self.autocomplete_tokens(&[Token::RBrace]);
+            if self.consume_token(&Token::RBrace) {
+                break;
+            }
+
+            let name = self.parse_ident()?;
+            let def = self.parse_type()?;
+            variants.push(NameAndType {
+                name,
+                def,
+                nullable: true,
+                doc: None,
+            });
+
+            self.autocomplete_tokens(&[Token::Comma, Token::RBrace]);
+            match self.next_token().token {
+                Token::Comma => {}
+                Token::RBrace => break,
+                _ => {
+                    return unexpected_token!(
+                        self.file.clone(),
+                        self.peek_token(),
+                        "Expected: ',' | '}}'"
+                    );
+                }
+            }
+        }
+        Ok(TypeBody::Union(variants))
+    }
+
     pub fn parse_expr(&mut self) -> Result<Expr> {
         let start = self.peek_start_location();
         self.sqlparser
@@ -652,6 +1179,10 @@ impl<'a> Parser<'a> {
         let is_unsafe = self.consume_keyword("unsafe");
 
         let body = match self.peek_token().token {
+            Token::Word(w) if w.value.to_lowercase() == "query" && self.peek_block(1) => {
+                self.next_token();
+                ExprBody::ModelQuery(self.parse_model_query()?)
+            }
             Token::Word(Word {
                 value: _,
                 quote_style: _,
@@ -1,8 +1,10 @@
 use std::{collections::BTreeMap, sync::Arc};
 
+use super::error::Result;
+use super::runtime::eval_param;
 use super::sql::{new_engine, SQLEngine, SQLEngineType};
 use crate::ast::Ident;
-use crate::compile::schema;
+use crate::compile::{extern_context_key, schema};
 use crate::types::Value;
 
 // A basic context with runtime state we can pass into functions. We may want
@@ -44,4 +46,62 @@ impl Context {
             ..self.clone()
         }
     }
+
+    // Binds a value that was supplied as a string (e.g. `--param start=2023-01-01` on the command
+    // line) to the extern with the given name, casting it to the extern's type. Externs of imported
+    // schemas are named by their import path, e.g. `lib.start`. Returns false if there is no such
+    // extern.
+    //
+    pub async fn bind_extern(
+        &mut self,
+        schema: &schema::SchemaRef,
+        name: &str,
+        value: &str,
+    ) -> Result<bool> {
+        let path = name.split('.').map(Ident::from).collect::<Vec<_>>();
+        let (key, type_) = match find_extern(schema, &path)? {
+            Some(found) => found,
+            None => return Ok(false),
+        };
+        let type_ = type_.must()?.read()?.to_runtime_type()?;
+
+        let value = eval_param(self, value, &type_).await?;
+        self.values.insert(key, value);
+        Ok(true)
+    }
+}
+
+// Finds the extern at the given path, which is either declared in the schema itself or (when the
+// path has more than one part) in a schema that it imports. Returns the context key that the
+// extern's value is supplied under, along with its type.
+//
+fn find_extern(
+    schema: &schema::SchemaRef,
+    path: &[Ident],
+) -> Result<Option<(Ident, schema::CRef<schema::MType>)>> {
+    let (name, prefix) = match path.split_last() {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+
+    let schema = schema.read()?;
+    if prefix.is_empty() {
+        return Ok(schema
+            .externs
+            .get(name)
+            .map(|type_| (extern_context_key(&schema.file, name), type_.clone())));
+    }
+
+    for i in 1..=prefix.len() {
+        if let Some(imported) = schema.imports.get(&prefix[..i].to_vec()) {
+            let imported = imported.read()?.schema.clone();
+            let mut rest = prefix[i..].to_vec();
+            rest.push(name.clone());
+            if let Some(found) = find_extern(&imported, &rest)? {
+                return Ok(Some(found));
+            }
+        }
+    }
+
+    Ok(None)
 }
@@ -56,7 +56,7 @@ impl TryInto<DFScalarValue> for Value {
                 let data_type = l.data_type();
                 DFScalarValue::List(
                     Some(
-                        l.as_vec()
+                        l.as_vec()?
                             .iter()
                             .map(|v| (v.clone()).try_into())
                             .collect::<Result<Vec<DFScalarValue>>>()?,
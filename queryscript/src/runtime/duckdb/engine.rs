@@ -17,7 +17,7 @@ use crate::runtime::{
     sql::{SQLEngine, SQLParam},
     Context,
 };
-use crate::types::{arrow::ArrowRecordBatchRelation, Relation, Value};
+use crate::types::{arrow::ArrowRecordBatchRelation, AtomicType, Relation, Type, Value};
 
 #[cxx::bridge]
 pub mod cppffi {
@@ -45,12 +45,23 @@ pub struct DuckDBNormalizer {
 }
 
 impl DuckDBNormalizer {
-    pub fn new(params: &[Ident]) -> DuckDBNormalizer {
+    pub fn new(params: &[(Ident, &Type)]) -> DuckDBNormalizer {
         DuckDBNormalizer {
             params: params
                 .iter()
                 .enumerate()
-                .map(|(i, s)| (s.to_string(), format!("${}", i + 1)))
+                .map(|(i, (s, type_))| {
+                    let placeholder = format!("${}", i + 1);
+                    let placeholder = match type_ {
+                        // DuckDB only binds naive timestamps, so we pass zoned timestamps as their
+                        // UTC instant and cast them back.
+                        Type::Atom(AtomicType::Timestamp(_, Some(_))) => {
+                            format!("CAST({} AS TIMESTAMPTZ)", placeholder)
+                        }
+                        _ => placeholder,
+                    };
+                    (s.to_string(), placeholder)
+                })
                 .collect(),
         }
     }
@@ -78,6 +89,12 @@ type RelationMap = HashMap<String, ArrowRelation>;
 pub struct DuckDBEngine();
 
 impl DuckDBEngine {
+    // The operators outside of standard SQL (e.g. `~`, `!`, or `@>`) that DuckDB accepts.
+    pub const OPERATORS: &'static [&'static str] = &["~", "!", "!!", "@", "<<", ">>", "@>", "<@"];
+
+    // DuckDB stores decimals in at most 128 bits.
+    pub const MAX_DECIMAL_PRECISION: u8 = 38;
+
     pub fn new() -> DuckDBEngine {
         DuckDBEngine()
     }
@@ -148,7 +165,12 @@ impl DuckDBEngine {
 
         scalar_params.sort();
 
-        let normalizer = DuckDBNormalizer::new(&scalar_params);
+        let normalizer = DuckDBNormalizer::new(
+            &scalar_params
+                .iter()
+                .map(|k| (k.clone(), &params.get(k).unwrap().type_))
+                .collect::<Vec<_>>(),
+        );
         let query = normalizer.normalize(&query);
         let query_string = format!("{}", query);
 
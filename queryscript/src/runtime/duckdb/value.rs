@@ -41,6 +41,8 @@ impl ToSql for Value {
             Self::FixedSizeBinary(_len, buf) => buf.to_sql()?,
             Self::LargeBinary(x) => x.to_sql()?,
 
+            // Zoned timestamps are UTC instants, which the normalizer casts back to TIMESTAMPTZ
+            // (see DuckDBNormalizer).
             Self::TimestampSecond(x, _tz) => {
                 ToSqlOutput::Owned(DuckValue::Timestamp(DuckTimeUnit::Second, *x))
             }
@@ -26,6 +26,15 @@ pub enum RuntimeError {
         backtrace: Option<Backtrace>,
     },
 
+    #[snafu(display(
+        "Field {} is declared not null, but the SQL engine returned NULL values for it",
+        field
+    ))]
+    NullabilityViolation {
+        field: String,
+        backtrace: Option<Backtrace>,
+    },
+
     #[snafu(context(false))]
     TypesystemError {
         #[snafu(backtrace)]
@@ -88,6 +97,10 @@ impl RuntimeError {
         }
         .build();
     }
+
+    pub fn nullability_violation(field: &str) -> RuntimeError {
+        return NullabilityViolationSnafu { field }.build();
+    }
 }
 
 impl Into<arrow::error::ArrowError> for RuntimeError {
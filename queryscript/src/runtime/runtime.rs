@@ -1,11 +1,12 @@
 use futures::future::{BoxFuture, FutureExt};
+use sqlparser::ast as sqlast;
 use std::collections::HashMap;
 
-use crate::compile::schema;
+use crate::compile::{extern_context_name, schema};
 use crate::{
     ast::Ident,
     types,
-    types::{Arc, Value},
+    types::{Arc, ArrowArray, Relation, Value},
 };
 
 use super::{context::Context, error::*, sql::SQLParam};
@@ -41,6 +42,47 @@ pub async fn eval_params<'a>(
     Ok(param_values)
 }
 
+// Evaluates a parameter that was supplied as a string (e.g. `--param start=2023-01-01` on the
+// command line) into a value of the given type, by casting it with the SQL engine.
+//
+pub async fn eval_param(ctx: &Context, value: &str, type_: &types::Type) -> Result<Value> {
+    let data_type: sqlast::DataType = type_.try_into()?;
+    let expr = schema::TypedExpr {
+        type_: schema::mkref(type_.clone()),
+        expr: Arc::new(schema::Expr::SQL(Arc::new(schema::SQL {
+            names: schema::SQLNames::new(),
+            body: schema::SQLBody::Expr(sqlast::Expr::Cast {
+                expr: Box::new(sqlast::Expr::Value(sqlast::Value::SingleQuotedString(
+                    value.to_string(),
+                ))),
+                data_type,
+            }),
+        }))),
+    };
+
+    eval(ctx, &expr).await
+}
+
+// Fields that are declared (or inferred to be) non-null must not contain any NULL values.
+fn check_nullability(expected_type: &types::Type, rows: &dyn Relation) -> Result<()> {
+    let fields = match expected_type {
+        types::Type::List(inner) => match inner.as_ref() {
+            types::Type::Record(fields) => fields,
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    for batch in (0..rows.num_batches()).map(|i| rows.batch(i).as_arrow_recordbatch()) {
+        for (field, column) in fields.iter().zip(batch.columns().iter()) {
+            if !field.nullable && column.null_count() > 0 {
+                return Err(RuntimeError::nullability_violation(field.name.as_str()));
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn eval<'a>(
     ctx: &'a Context,
     typed_expr: &'a schema::TypedExpr<TypeRef>,
@@ -69,7 +111,11 @@ pub fn eval<'a>(
             schema::Expr::ContextRef(r) => match ctx.values.get(r) {
                 Some(v) => Ok(v.clone()), // Can we avoid this clone??
                 None => Err(RuntimeError::new(
-                    format!("No such context value {}", r).as_str(),
+                    match extern_context_name(r) {
+                        Some(name) => format!("No value supplied for extern {}", name),
+                        None => format!("No such context value {}", r),
+                    }
+                    .as_str(),
                 )),
             },
             schema::Expr::Fn(f) => {
@@ -138,10 +184,12 @@ pub fn eval<'a>(
                             return fail!("Expected an expression to have exactly one column");
                         }
 
-                        let row = &rows.batch(0).records()[0];
+                        let row = &rows.batch(0).records()?[0];
                         let value = row.column(0).clone();
                         let value_type = value.type_();
-                        if !ctx.disable_typechecks && *expected_type != value_type {
+                        if !ctx.disable_typechecks
+                            && !expected_type.eq_ignoring_nullability(&value_type)
+                        {
                             return Err(RuntimeError::type_mismatch(
                                 expected_type.clone(),
                                 value_type,
@@ -156,12 +204,13 @@ pub fn eval<'a>(
                             let rows_type = crate::types::Type::List(Box::new(
                                 crate::types::Type::Record(rows.schema()),
                             ));
-                            if *expected_type != rows_type {
+                            if !expected_type.eq_ignoring_nullability(&rows_type) {
                                 return Err(RuntimeError::type_mismatch(
                                     expected_type.clone(),
                                     rows_type,
                                 ));
                             }
+                            check_nullability(&*expected_type, rows.as_ref())?;
                         }
 
                         Ok(Value::Relation(rows))
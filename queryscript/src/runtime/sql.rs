@@ -39,7 +39,7 @@ impl SQLParam {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum SQLEngineType {
     DuckDB,
 }
@@ -56,6 +56,24 @@ impl SQLEngineType {
             }
         })
     }
+
+    /// Returns whether the engine accepts an operator that is outside of standard SQL
+    /// (e.g. `~`, `!`, or `@>`).
+    pub fn supports_operator(&self, op: &str) -> bool {
+        use SQLEngineType::*;
+        let operators = match self {
+            DuckDB => super::duckdb::DuckDBEngine::OPERATORS,
+        };
+        operators.contains(&op)
+    }
+
+    /// Returns the largest precision of a decimal that the engine can store.
+    pub fn max_decimal_precision(&self) -> u8 {
+        use SQLEngineType::*;
+        match self {
+            DuckDB => super::duckdb::DuckDBEngine::MAX_DECIMAL_PRECISION,
+        }
+    }
 }
 
 pub fn new_engine(kind: SQLEngineType) -> Arc<dyn SQLEngine> {
@@ -6,10 +6,11 @@ use arrow::{
         Time64NanosecondArray, TimestampMicrosecondArray, TimestampMillisecondArray,
         TimestampNanosecondArray, TimestampSecondArray,
     },
-    datatypes::ArrowPrimitiveType,
+    datatypes::{ArrowPrimitiveType, DataType as ArrowDataType},
 };
 use arrow_schema::SchemaRef as ArrowSchemaRef;
 
+use super::error::{ts_fail, Result};
 use super::list::VecList;
 use super::record::VecRow;
 use super::types::{try_arrow_fields_to_fields, Field, Type};
@@ -59,21 +60,21 @@ impl RecordBatch for ArrowRecordBatch {
         self
     }
 
-    fn records(&self) -> Vec<Arc<dyn Record>> {
+    fn records(&self) -> Result<Vec<Arc<dyn Record>>> {
         let schema = Arc::new(RecordBatch::schema(self));
         let mut columns: Vec<_> = self
             .columns()
             .iter()
-            .map(|col| col.as_vec().into_iter())
-            .collect();
-        (0..(self.num_rows()))
+            .map(|col| Ok(col.as_vec()?.into_iter()))
+            .collect::<Result<_>>()?;
+        Ok((0..(self.num_rows()))
             .map(|_| {
                 VecRow::new(
                     schema.clone(),
                     columns.iter_mut().map(|col| col.next().unwrap()).collect(),
                 )
             })
-            .collect()
+            .collect())
     }
 
     fn as_arrow_recordbatch(&self) -> &ArrowRecordBatch {
@@ -101,7 +102,7 @@ impl<'a, T: ArrowArray> List for &'a T {
         T::as_any(self)
     }
 
-    fn as_vec(&self) -> Vec<Value> {
+    fn as_vec(&self) -> Result<Vec<Value>> {
         use super::ArrowDataType::*;
         use arrow::array::*;
         use arrow::datatypes::*;
@@ -144,11 +145,15 @@ impl<'a, T: ArrowArray> List for &'a T {
             },
             Date32 => downcast_to_vec!(self, Date32Array),
             Date64 => downcast_to_vec!(self, Date64Array),
-            Time64(u) => match u {
+            Time32(u) => match u {
                 TimeUnit::Second => downcast_to_vec!(self, Time32SecondArray),
                 TimeUnit::Millisecond => downcast_to_vec!(self, Time32MillisecondArray),
+                _ => return ts_fail!("invalid array type {:?}", T::data_type(self)),
+            },
+            Time64(u) => match u {
                 TimeUnit::Microsecond => downcast_to_vec!(self, Time64MicrosecondArray),
                 TimeUnit::Nanosecond => downcast_to_vec!(self, Time64NanosecondArray),
+                _ => return ts_fail!("invalid array type {:?}", T::data_type(self)),
             },
             Interval(u) => match u {
                 IntervalUnit::YearMonth => downcast_to_vec!(self, IntervalYearMonthArray),
@@ -158,54 +163,64 @@ impl<'a, T: ArrowArray> List for &'a T {
             Decimal128(..) => as_primitive_array::<Decimal128Type>(self).into(),
             Decimal256(..) => as_primitive_array::<Decimal256Type>(self).into(),
             List(field_type) => {
-                let dt: Arc<Type> = Arc::new(field_type.data_type().try_into().unwrap());
+                let dt: Arc<Type> = Arc::new(field_type.data_type().try_into()?);
                 VecWrapper(
                     as_list_array(self)
                         .iter()
-                        .map(|x| match x {
-                            Some(v) => Value::List(VecList::new(dt.clone(), (&v).as_vec())),
-                            None => Value::Null,
+                        .map(|x| {
+                            Ok(match x {
+                                Some(v) => Value::List(VecList::new(dt.clone(), (&v).as_vec()?)),
+                                None => Value::Null,
+                            })
                         })
-                        .collect(),
+                        .collect::<Result<_>>()?,
                 )
             }
             LargeList(field_type) => {
-                let dt: Arc<Type> = Arc::new(field_type.data_type().try_into().unwrap());
+                let dt: Arc<Type> = Arc::new(field_type.data_type().try_into()?);
                 VecWrapper(
                     as_large_list_array(self)
                         .iter()
-                        .map(|x| match x {
-                            Some(v) => Value::List(VecList::new(dt.clone(), (&v).as_vec())),
-                            None => Value::Null,
+                        .map(|x| {
+                            Ok(match x {
+                                Some(v) => Value::List(VecList::new(dt.clone(), (&v).as_vec()?)),
+                                None => Value::Null,
+                            })
                         })
-                        .collect(),
+                        .collect::<Result<_>>()?,
                 )
             }
             FixedSizeList(field_type, _) => {
-                let dt: Arc<Type> = Arc::new(field_type.data_type().try_into().unwrap());
-                let fsl = T::as_any(self)
-                    .downcast_ref::<FixedSizeListArray>()
-                    .expect("Unable to downcast to fixed size list array");
+                let dt: Arc<Type> = Arc::new(field_type.data_type().try_into()?);
+                let fsl = match T::as_any(self).downcast_ref::<FixedSizeListArray>() {
+                    Some(fsl) => fsl,
+                    None => return ts_fail!("Unable to downcast to fixed size list array"),
+                };
                 VecWrapper(
                     (0..(fsl.len()))
-                        .map(|i| Value::List(VecList::new(dt.clone(), (&fsl.value(i)).as_vec())))
-                        .collect(),
+                        .map(|i| {
+                            Ok(Value::List(VecList::new(
+                                dt.clone(),
+                                (&fsl.value(i)).as_vec()?,
+                            )))
+                        })
+                        .collect::<Result<_>>()?,
                 )
             }
             Struct(fields) => {
-                let schema: Arc<Vec<super::types::Field>> = Arc::new(
-                    try_arrow_fields_to_fields(fields).expect("Failed to convert struct fields"),
-                );
-                let struct_array = T::as_any(self)
-                    .downcast_ref::<StructArray>()
-                    .expect("Unable to downcast to struct array");
+                let schema: Arc<Vec<super::types::Field>> =
+                    Arc::new(try_arrow_fields_to_fields(fields)?);
+                let struct_array = match T::as_any(self).downcast_ref::<StructArray>() {
+                    Some(struct_array) => struct_array,
+                    None => return ts_fail!("Unable to downcast to struct array"),
+                };
 
                 // NOTE: This is the same fundamental operation as RecordBatch::records
                 let mut columns: Vec<_> = struct_array
                     .columns()
                     .iter()
-                    .map(|col| col.as_vec().into_iter())
-                    .collect();
+                    .map(|col| Ok(col.as_vec()?.into_iter()))
+                    .collect::<Result<_>>()?;
 
                 VecWrapper(
                     (0..(struct_array.len()))
@@ -218,11 +233,38 @@ impl<'a, T: ArrowArray> List for &'a T {
                         .collect(),
                 )
             }
-            Union(..) | Dictionary(..) | Map(..) | Time32(..) | Duration(..) => {
+            // Dictionary arrays are decoded into their values, since dictionary encoding does not
+            // change the value of each element.
+            Dictionary(_, value_type) => {
+                let array = make_array(self.data().clone());
+                let decoded = match arrow::compute::cast(&array, value_type.as_ref()) {
+                    Ok(decoded) => decoded,
+                    Err(e) => return ts_fail!("Unable to decode dictionary array: {}", e),
+                };
+                VecWrapper((&decoded).as_vec()?)
+            }
+            // Each element of a union array is the value of its active member.
+            Union(..) => {
+                let union_array = match T::as_any(self).downcast_ref::<UnionArray>() {
+                    Some(union_array) => union_array,
+                    None => return ts_fail!("Unable to downcast to union array"),
+                };
+                VecWrapper(
+                    (0..(union_array.len()))
+                        .map(|i| {
+                            Ok(match (&union_array.value(i)).as_vec()?.into_iter().next() {
+                                Some(v) => v,
+                                None => Value::Null,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            Map(..) | Duration(..) => {
                 panic!("unsupported array type {:?}", T::data_type(self))
             }
         };
-        vec_list.0
+        Ok(vec_list.0)
     }
 }
 
@@ -285,10 +327,16 @@ macro_rules! timestamp_array_conversion {
     ($array_ty:ty, $arm:tt) => {
         impl ArrayConvert for $array_ty {
             fn to_vec(&self) -> VecWrapper {
+                // The values are UTC instants (or naive, if there is no timezone), so we just need
+                // to carry the timezone along for display.
+                let tz = match self.data_type() {
+                    ArrowDataType::Timestamp(_, tz) => tz.clone(),
+                    _ => None,
+                };
                 VecWrapper(
                     self.iter()
                         .map(|x| match x {
-                            Some(v) => Value::$arm(v.into(), None),
+                            Some(v) => Value::$arm(v.into(), tz.clone()),
                             None => Value::Null,
                         })
                         .collect(),
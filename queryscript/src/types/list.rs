@@ -1,3 +1,4 @@
+use super::error::Result;
 use super::types::*;
 use super::value::*;
 
@@ -22,7 +23,7 @@ impl List for VecList {
         self
     }
 
-    fn as_vec(&self) -> Vec<Value> {
-        self.values.clone()
+    fn as_vec(&self) -> Result<Vec<Value>> {
+        Ok(self.values.clone())
     }
 }
@@ -33,7 +33,7 @@ impl Serialize for Value {
             Self::LargeBinary(x) => x.serialize(serializer),
 
             Self::List(l) => {
-                let v = l.as_vec();
+                let v = l.as_vec().map_err(serde::ser::Error::custom)?;
 
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
                 for e in v.iter() {
@@ -43,9 +43,7 @@ impl Serialize for Value {
             }
 
             Self::Relation(r) => {
-                let v = (0..r.num_batches())
-                    .flat_map(|i| r.batch(i).records())
-                    .collect::<Vec<_>>();
+                let v = r.records().map_err(serde::ser::Error::custom)?;
 
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
                 for e in v.iter() {
@@ -1,6 +1,6 @@
 pub use arrow::datatypes::{
     DataType as ArrowDataType, Field as ArrowField, IntervalUnit as ArrowIntervalUnit,
-    Schema as ArrowSchema, TimeUnit as ArrowTimeUnit,
+    Schema as ArrowSchema, TimeUnit as ArrowTimeUnit, UnionMode,
 };
 use sqlparser::ast::{
     DataType as ParserDataType, ExactNumberInfo as ParserNumberInfo, TimezoneInfo as ParserTz,
@@ -17,6 +17,38 @@ pub enum Type {
     Record(Vec<Field>),
     List(Box<Type>),
     Fn(FnType),
+    // A tagged union, where each value is exactly one of the (named) fields. This is represented
+    // as a dense Arrow union whose type ids are the indices of the fields.
+    Union(Vec<Field>),
+}
+
+impl Type {
+    // Compares two types without considering the nullability of their fields. SQL engines do not
+    // reliably report whether a field can be NULL, so nullability is checked against the data
+    // instead. An enum whose members are unknown (i.e. one that was read back from Arrow) matches
+    // any enum.
+    pub fn eq_ignoring_nullability(&self, other: &Type) -> bool {
+        let fields_eq = |l: &Vec<Field>, r: &Vec<Field>| {
+            l.len() == r.len()
+                && l.iter()
+                    .zip(r.iter())
+                    .all(|(l, r)| l.name == r.name && l.type_.eq_ignoring_nullability(&r.type_))
+        };
+        match (self, other) {
+            (Type::Atom(AtomicType::Enum(l)), Type::Atom(AtomicType::Enum(r))) => {
+                l == r || l.is_empty() || r.is_empty()
+            }
+            (Type::Atom(l), Type::Atom(r)) => l == r,
+            (Type::Record(l), Type::Record(r)) | (Type::Union(l), Type::Union(r)) => {
+                fields_eq(l, r)
+            }
+            (Type::List(l), Type::List(r)) => l.eq_ignoring_nullability(r),
+            (Type::Fn(l), Type::Fn(r)) => {
+                fields_eq(&l.args, &r.args) && l.ret.eq_ignoring_nullability(&r.ret)
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -196,13 +228,25 @@ pub enum AtomicType {
     ///
     /// For example the number 123.45 has precision 5 and scale 2.
     Decimal256(u8, u8),
+    /// A dictionary encoded value (`key_type`, `value_type`), where each value is stored as an
+    /// index of `key_type` into an associated dictionary of `value_type`.
+    ///
+    /// This is mostly used to represent low cardinality strings, and otherwise behaves like a
+    /// value of `value_type`.
+    Dictionary(Box<AtomicType>, Box<AtomicType>),
+    /// A string that must be one of a fixed set of members. It is stored as a dictionary of
+    /// strings, whose key type is the smallest unsigned integer that can index the members.
+    ///
+    /// The members are not part of the Arrow type, so an enum that is read back from Arrow (e.g.
+    /// DuckDB's ENUM type) has no members, which means that they are unknown.
+    Enum(Vec<String>),
     //
     // The Arrow DataType struct contains several composite types, which we break out
     // this enum. Several of them we exclude entirely.
-    // List and Struct are in the top-level type field
+    // List and Struct are in the top-level type field, and Union is a top-level type too, since
+    // its members may be any type.
     // FixedSizeList, LargeList, and Map are ignored (because I think they are just
     // implementation-specific representations of List and Struct)
-    // TODO Union and Dictionary are two useful types that we should eventually implement.
     //
     /*
     /// A list of some logical data type with variable length.
@@ -331,6 +375,7 @@ impl TryFrom<&ArrowDataType> for Type {
             Timestamp(u, s) => Type::Atom(AtomicType::Timestamp(u.into(), s.clone())),
             Date32 => Type::Atom(AtomicType::Date32),
             Date64 => Type::Atom(AtomicType::Date64),
+            Time32(u) => Type::Atom(AtomicType::Time32(u.into())),
             Time64(u) => Type::Atom(AtomicType::Time64(u.into())),
             Interval(u) => Type::Atom(AtomicType::Interval(u.into())),
             Binary => Type::Atom(AtomicType::Binary),
@@ -344,9 +389,22 @@ impl TryFrom<&ArrowDataType> for Type {
                 Type::List(Box::new(f.data_type().try_into()?))
             }
             Struct(fields) => fields.try_into()?,
-            Union(..) | Dictionary(..) | Map(..) | Time32(..) | Duration(..) => {
-                return ts_unimplemented!("type {:?}", &t)
+            Union(fields, _, _) => Type::Union(try_arrow_fields_to_fields(fields)?),
+            Dictionary(key, value)
+                if matches!(key.as_ref(), UInt8 | UInt16 | UInt32) && value.as_ref() == &Utf8 =>
+            {
+                Type::Atom(AtomicType::Enum(Vec::new()))
             }
+            Dictionary(key, value) => match (
+                Type::try_from(key.as_ref())?,
+                Type::try_from(value.as_ref())?,
+            ) {
+                (Type::Atom(key), Type::Atom(value)) => {
+                    Type::Atom(AtomicType::Dictionary(Box::new(key), Box::new(value)))
+                }
+                _ => return ts_unimplemented!("type {:?}", &t),
+            },
+            Map(..) | Duration(..) => return ts_unimplemented!("type {:?}", &t),
         })
     }
 }
@@ -384,6 +442,20 @@ impl TryInto<ArrowDataType> for &Type {
             Atom(LargeUtf8) => ArrowDataType::LargeUtf8,
             Atom(Decimal128(p, s)) => ArrowDataType::Decimal128(*p, *s),
             Atom(Decimal256(p, s)) => ArrowDataType::Decimal256(*p, *s),
+            Atom(Dictionary(key, value)) => ArrowDataType::Dictionary(
+                Box::new((&Atom(key.as_ref().clone())).try_into()?),
+                Box::new((&Atom(value.as_ref().clone())).try_into()?),
+            ),
+            Atom(Enum(members)) => ArrowDataType::Dictionary(
+                Box::new(if members.len() <= u8::MAX as usize {
+                    ArrowDataType::UInt8
+                } else if members.len() <= u16::MAX as usize {
+                    ArrowDataType::UInt16
+                } else {
+                    ArrowDataType::UInt32
+                }),
+                Box::new(ArrowDataType::Utf8),
+            ),
             Record(fields) => ArrowDataType::Struct(
                 fields
                     .iter()
@@ -395,6 +467,11 @@ impl TryInto<ArrowDataType> for &Type {
                 data_type.as_ref().try_into()?,
                 true,
             ))),
+            Union(fields) => ArrowDataType::Union(
+                try_fields_to_arrow_fields(fields)?,
+                (0..fields.len()).map(|i| i as i8).collect(),
+                UnionMode::Dense,
+            ),
             Fn(_) => return ts_fail!("Arrow does not support function types"),
         })
     }
@@ -453,8 +530,12 @@ impl TryInto<ParserDataType> for &Type {
             Atom(Decimal256(p, s)) => {
                 ParserDataType::Decimal(ParserNumberInfo::PrecisionAndScale(*p as u64, *s as u64))
             }
+            // Dictionary values are decoded to their value type when they're cast
+            Atom(Dictionary(_, value)) => (&Atom(value.as_ref().clone())).try_into()?,
+            Atom(Enum(_)) => ParserDataType::String,
             List(_data_type) => return ts_fail!("Parser does not support list types"),
             Record(_) => return ts_fail!("Parser does not support record types"),
+            Union(_) => return ts_fail!("Parser does not support union types"),
             Fn(_) => return ts_fail!("Parser does not support function types"),
         })
     }
@@ -9,7 +9,8 @@ pub use arrow::{
 };
 pub use arrow_buffer::i256;
 use async_trait::async_trait;
-use chrono::{TimeZone, Utc};
+use chrono::{FixedOffset, TimeZone, Utc};
+use chrono_tz::Tz;
 use dyn_clone::{clone_trait_object, DynClone};
 use futures::future::BoxFuture;
 pub use std::any::Any;
@@ -85,7 +86,7 @@ pub trait List: fmt::Debug + Send + Sync {
 
     // TODO: This should eventually be changed to have the standard
     // array-like methods (indexing, etc.)
-    fn as_vec(&self) -> Vec<Value>;
+    fn as_vec(&self) -> super::error::Result<Vec<Value>>;
 }
 
 #[async_trait]
@@ -108,11 +109,12 @@ pub trait Relation: fmt::Debug + Send + Sync {
     fn num_batches(&self) -> usize;
     fn batch(&self, index: usize) -> &dyn RecordBatch;
 
-    fn records(&self) -> Vec<Arc<dyn Record>> {
-        (0..self.num_batches())
-            .map(|i| self.batch(i).records())
-            .flatten()
-            .collect()
+    fn records(&self) -> super::error::Result<Vec<Arc<dyn Record>>> {
+        let mut records = Vec::new();
+        for i in 0..self.num_batches() {
+            records.extend(self.batch(i).records()?);
+        }
+        Ok(records)
     }
 }
 
@@ -120,7 +122,7 @@ pub trait RecordBatch: fmt::Debug + Send + Sync {
     fn schema(&self) -> Vec<Field>;
     fn as_any(&self) -> &dyn Any;
 
-    fn records(&self) -> Vec<Arc<dyn Record>>;
+    fn records(&self) -> super::error::Result<Vec<Arc<dyn Record>>>;
     fn as_arrow_recordbatch(&self) -> &ArrowRecordBatch;
 }
 
@@ -235,6 +237,33 @@ impl Value {
     }
 }
 
+// The timezone of a timestamp, which Arrow allows to be either a name from the tz database
+// (e.g. "America/New_York") or a fixed offset (e.g. "+05:30").
+//
+enum Timezone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+fn parse_timezone(tz: &str) -> Option<Timezone> {
+    if let Ok(tz) = tz.parse::<Tz>() {
+        return Some(Timezone::Named(tz));
+    }
+
+    let (sign, rest) = match tz.chars().next() {
+        Some('+') => (1, &tz[1..]),
+        Some('-') => (-1, &tz[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let seconds = hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60;
+    FixedOffset::east_opt(sign * seconds).map(Timezone::Fixed)
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -266,10 +295,10 @@ impl fmt::Display for Value {
             Self::FixedSizeBinary(_len, buf) => write!(f, "{:?}", buf),
             Self::LargeBinary(x) => write!(f, "{:?}", x),
 
-            Self::TimestampSecond(x, _tz)
-            | Self::TimestampMillisecond(x, _tz)
-            | Self::TimestampMicrosecond(x, _tz)
-            | Self::TimestampNanosecond(x, _tz) => {
+            Self::TimestampSecond(x, tz)
+            | Self::TimestampMillisecond(x, tz)
+            | Self::TimestampMicrosecond(x, tz)
+            | Self::TimestampNanosecond(x, tz) => {
                 let (seconds, nanos) = match &self {
                     Self::TimestampSecond(..) => (*x, 0),
                     Self::TimestampMillisecond(..) => (*x / 1000, (*x % 1000) * 1_000_000),
@@ -277,13 +306,16 @@ impl fmt::Display for Value {
                     Self::TimestampNanosecond(..) => (*x / 1_000_000_000, *x % 1_000_000_000),
                     _ => panic!("unreachable"),
                 };
-                write!(
-                    f,
-                    "{}",
-                    Utc.timestamp_opt(seconds, nanos as u32)
-                        .unwrap()
-                        .to_rfc3339()
-                )
+                let ts = Utc.timestamp_opt(seconds, nanos as u32).unwrap();
+                match tz.as_ref().map(|tz| parse_timezone(tz)) {
+                    Some(Some(Timezone::Named(tz))) => {
+                        write!(f, "{}", ts.with_timezone(&tz).to_rfc3339())
+                    }
+                    Some(Some(Timezone::Fixed(offset))) => {
+                        write!(f, "{}", ts.with_timezone(&offset).to_rfc3339())
+                    }
+                    _ => write!(f, "{}", ts.to_rfc3339()),
+                }
             }
 
             Self::Date32(x) => write!(
@@ -327,7 +359,7 @@ impl fmt::Display for Value {
                 let mut builder = TableBuilder::default();
                 builder.set_columns(schema.iter().map(|f| Cow::Borrowed(f.name.as_str())));
                 for idx in 0..r.num_batches() {
-                    for record in r.batch(idx).records().into_iter() {
+                    for record in r.batch(idx).records().map_err(|_| fmt::Error)? {
                         builder.add_record(
                             (0..ncols)
                                 .map(|col_idx| Cow::Owned(format!("{}", record.column(col_idx)))),
@@ -342,7 +374,7 @@ impl fmt::Display for Value {
 
             // TODO: Implement list without Debug
             Self::List(l) => {
-                write!(f, "{:?}", l.as_vec())
+                write!(f, "{:?}", l.as_vec().map_err(|_| fmt::Error)?)
             }
 
             // TODO: Implement functions without Debug
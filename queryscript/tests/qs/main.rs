@@ -239,6 +239,43 @@ mod tests {
         }
     }
 
+    // Without inlining, calls to functions passed as arguments within SQL can't be compiled, since
+    // the engine can't call function values.
+    //
+    #[test]
+    fn test_function_arguments_without_inlining() {
+        let prefix = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/qs/simple/");
+        let fpath = prefix.join("higher_order.qs");
+
+        let compiler = compile::Compiler::new_with_config(compile::CompilerConfig {
+            allow_inlining: false,
+            ..Default::default()
+        })
+        .expect("Failed to create compiler");
+        let schema = compile::Schema::new(
+            String::from(fpath.to_str().unwrap()),
+            Some(String::from(prefix.to_str().unwrap())),
+        );
+
+        let text = fs::read_to_string(&fpath).expect("Failed to read file");
+        let ast = parser::parse_schema(fpath.to_str().unwrap(), &text).unwrap();
+        let result = compiler.compile_schema_ast(schema.clone(), &ast);
+
+        let errors = result
+            .errors
+            .iter()
+            .map(|(_, e)| e.to_string())
+            .collect::<Vec<_>>();
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.starts_with("Calling the function argument")
+                    && e.ends_with("requires inlining, which is disabled")),
+            "Missing inlining error in {:?}",
+            errors
+        );
+    }
+
     #[test]
     fn test_double_schemas() {
         let prefix = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/qs/jaffle/");
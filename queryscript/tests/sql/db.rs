@@ -205,7 +205,7 @@ trait IntoDBOutput {
 impl IntoDBOutput for &dyn queryscript::types::Relation {
     fn into_db_output(self) -> DBOutput {
         let schema = self.schema();
-        let records = self.records();
+        let records = self.records().expect("Failed to decode records");
 
         // This is a bit of a hack to try to guess that it's a statement
         if schema.len() == 1
@@ -263,12 +263,15 @@ fn to_columntype(type_: &queryscript::types::Type) -> sqllogictest::runner::Colu
         | Atom(FixedSizeBinary(..))
         | Atom(LargeBinary)
         | Atom(Utf8)
-        | Atom(LargeUtf8) => ColumnType::Text,
+        | Atom(LargeUtf8)
+        | Atom(Enum(_)) => ColumnType::Text,
 
         Atom(Decimal128(..)) | Atom(Decimal256(..)) => ColumnType::FloatingPoint,
+        Atom(Dictionary(_, value)) => to_columntype(&Atom(value.as_ref().clone())),
 
         List(_data_type) => ColumnType::Unknown('l'),
         Record(_) => ColumnType::Unknown('r'),
         Fn(_) => ColumnType::Unknown('f'),
+        Union(_) => ColumnType::Unknown('u'),
     }
 }